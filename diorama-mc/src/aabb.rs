@@ -0,0 +1,73 @@
+use crate::ray::Ray;
+use nalgebra_glm::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Self {
+        Aabb { min, max }
+    }
+
+    // Caja "invertida": cualquier unión con ella devuelve la otra caja
+    pub fn empty() -> Self {
+        Aabb {
+            min: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+        }
+    }
+
+    // Para primitivas sin límites, como el plano infinito
+    pub fn infinite() -> Self {
+        Aabb {
+            min: Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY),
+            max: Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+        }
+    }
+
+    pub fn is_finite(&self) -> bool {
+        self.min.iter().chain(self.max.iter()).all(|c| c.is_finite())
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: self.min.inf(&other.min),
+            max: self.max.sup(&other.max),
+        }
+    }
+
+    pub fn grow(&mut self, point: &Vec3) {
+        self.min = self.min.inf(point);
+        self.max = self.max.sup(point);
+    }
+
+    pub fn centroid(&self) -> Vec3 {
+        (self.min + self.max) * 0.5
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.max - self.min;
+        if size.x < 0.0 || size.y < 0.0 || size.z < 0.0 {
+            return 0.0;
+        }
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    // Prueba de slabs; devuelve la distancia de entrada si el rayo toca la caja dentro de su rango
+    pub fn hit(&self, ray: &Ray, inv_dir: &Vec3) -> Option<f32> {
+        let t0 = (self.min - ray.origin).component_mul(inv_dir);
+        let t1 = (self.max - ray.origin).component_mul(inv_dir);
+
+        let t_enter = t0.x.min(t1.x).max(t0.y.min(t1.y)).max(t0.z.min(t1.z));
+        let t_exit = t0.x.max(t1.x).min(t0.y.max(t1.y)).min(t0.z.max(t1.z));
+
+        if t_enter <= t_exit && t_exit > ray.t_min && t_enter < ray.t_max {
+            Some(t_enter)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,171 @@
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+// Minecraft cuenta el tiempo de las animaciones en ticks de juego
+pub const TICKS_PER_SECOND: f32 = 20.0;
+
+// Un paso de la animación: qué cuadro de la imagen se muestra y durante cuántos ticks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationFrame {
+    pub index: usize,
+    pub ticks: u32,
+}
+
+// Animación por cuadros (flipbook) al estilo de los .mcmeta de Minecraft: los cuadros van
+// apilados verticalmente en la imagen y se recorren en orden según `frames`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Animation {
+    pub frames: Vec<AnimationFrame>,
+    pub interpolate: bool, // Mezcla cada cuadro con el siguiente en vez de saltar de golpe
+}
+
+impl Animation {
+    // Todos los cuadros en orden con la misma duración
+    pub fn new(frame_count: usize, frame_ticks: u32, interpolate: bool) -> Self {
+        Animation {
+            frames: (0..frame_count)
+                .map(|index| AnimationFrame {
+                    index,
+                    ticks: frame_ticks.max(1),
+                })
+                .collect(),
+            interpolate,
+        }
+    }
+
+    // Lee `<imagen>.mcmeta`, si existe, y devuelve la animación con el tamaño de cada cuadro.
+    // Como en Minecraft, sin `width`/`height` los cuadros son cuadrados del ancho de la imagen.
+    // Un .mcmeta que no se entiende deja la textura fija con un aviso, igual que el cargador de MTL
+    pub fn load_mcmeta(image_path: &str, image_width: usize, image_height: usize) -> Option<(Animation, usize, usize)> {
+        let meta_path = format!("{}.mcmeta", image_path);
+        if !Path::new(&meta_path).exists() {
+            return None;
+        }
+        let static_texture = |problem: String| {
+            eprintln!("Warning: {} in {}, using a static texture", problem, meta_path);
+            None
+        };
+
+        let meta: Value = match fs::read_to_string(&meta_path).map(|source| serde_json::from_str(&source)) {
+            Ok(Ok(meta)) => meta,
+            Ok(Err(err)) => return static_texture(format!("invalid JSON ({})", err)),
+            Err(err) => return static_texture(format!("unreadable file ({})", err)),
+        };
+        let animation = meta.get("animation")?;
+
+        let frame_width = read_usize(animation, "width").unwrap_or(image_width);
+        let frame_height = read_usize(animation, "height").unwrap_or(frame_width);
+        if frame_width == 0 || frame_height == 0 || frame_width > image_width || frame_height > image_height {
+            return static_texture(format!(
+                "frame size {}x{} does not fit a {}x{} image",
+                frame_width, frame_height, image_width, image_height
+            ));
+        }
+        let frame_count = image_height / frame_height;
+
+        let frame_ticks = read_usize(animation, "frametime").unwrap_or(1) as u32;
+        let interpolate = animation.get("interpolate").and_then(Value::as_bool).unwrap_or(false);
+
+        // Cada entrada de `frames` es un índice o un objeto {"index", "time"}; las que no sirven se saltan
+        let frames: Vec<AnimationFrame> = match animation.get("frames").and_then(Value::as_array) {
+            Some(list) => list
+                .iter()
+                .filter_map(|entry| {
+                    let (index, ticks) = match entry.as_u64() {
+                        Some(index) => (Some(index as usize), frame_ticks),
+                        None => (
+                            read_usize(entry, "index"),
+                            read_usize(entry, "time").map_or(frame_ticks, |time| time as u32),
+                        ),
+                    };
+                    match index {
+                        Some(index) if index < frame_count => Some(AnimationFrame {
+                            index,
+                            ticks: ticks.max(1),
+                        }),
+                        _ => {
+                            eprintln!("Warning: skipping invalid frame {} in {}", entry, meta_path);
+                            None
+                        }
+                    }
+                })
+                .collect(),
+            None => Animation::new(frame_count, frame_ticks, interpolate).frames,
+        };
+
+        if frames.is_empty() {
+            return static_texture("animation without frames".to_string());
+        }
+
+        Some((Animation { frames, interpolate }, frame_width, frame_height))
+    }
+
+    pub fn total_ticks(&self) -> u32 {
+        self.frames.iter().map(|frame| frame.ticks).sum()
+    }
+
+    // Cuadro que se ve en el instante `clock` (en segundos), el que le sigue y cuánto pesa este último
+    // Sin cuadros se ve siempre el primero de la imagen
+    pub fn frame_at(&self, clock: f32) -> (usize, usize, f32) {
+        let last = match self.frames.len().checked_sub(1) {
+            Some(last) => last,
+            None => return (0, 0, 0.0),
+        };
+        let total = self.total_ticks().max(1) as f32;
+        let mut tick = (clock * TICKS_PER_SECOND).rem_euclid(total);
+        let mut i = 0;
+        while i < last && tick >= self.frames[i].ticks as f32 {
+            tick -= self.frames[i].ticks as f32;
+            i += 1;
+        }
+        let ticks = self.frames[i].ticks.max(1) as f32;
+        let next = self.frames[(i + 1) % self.frames.len()].index;
+        let blend = if self.interpolate { (tick / ticks).clamp(0.0, 1.0) } else { 0.0 };
+        (self.frames[i].index, next, blend)
+    }
+}
+
+fn read_usize(value: &Value, key: &str) -> Option<usize> {
+    value.get(key).and_then(Value::as_u64).map(|n| n as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Escribe `<nombre>.mcmeta` en una carpeta temporal y devuelve la ruta de la imagen
+    fn image_with_meta(name: &str, meta: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("diorama-mcmeta-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let image = dir.join(format!("{}.png", name));
+        fs::write(format!("{}.mcmeta", image.display()), meta).unwrap();
+        image.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn malformed_mcmeta_gives_a_static_texture() {
+        for (name, meta) in [
+            ("json", "{ \"animation\": "),
+            ("size", "{ \"animation\": { \"width\": 32 } }"),
+            ("frames", "{ \"animation\": { \"frames\": [7, { \"time\": 2 }] } }"),
+        ] {
+            assert!(Animation::load_mcmeta(&image_with_meta(name, meta), 16, 64).is_none(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn invalid_frames_are_skipped() {
+        let meta = "{ \"animation\": { \"frametime\": 2, \"frames\": [3, 9, { \"index\": 1, \"time\": 5 }, \"x\"] } }";
+        let (animation, width, height) = Animation::load_mcmeta(&image_with_meta("skip", meta), 16, 64).unwrap();
+        assert_eq!((width, height), (16, 16));
+        assert_eq!(
+            animation.frames,
+            vec![AnimationFrame { index: 3, ticks: 2 }, AnimationFrame { index: 1, ticks: 5 }]
+        );
+        // 2 ticks del cuadro 3 y luego 5 del cuadro 1
+        assert_eq!(animation.frame_at(0.05).0, 3);
+        assert_eq!(animation.frame_at(0.15).0, 1);
+        assert_eq!(animation.frame_at(0.4).0, 3);
+    }
+}
@@ -0,0 +1,162 @@
+extern crate image;
+use crate::texture::Texture;
+use image::{DynamicImage, GenericImageView, ImageReader, RgbaImage};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+// Rectángulo de una tesela dentro del atlas, en UV del atlas completo (v hacia arriba)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AtlasTile {
+    pub u_min: f32,
+    pub v_min: f32,
+    pub u_max: f32,
+    pub v_max: f32,
+    margin_u: f32, // Medio texel: la UV remapeada nunca pasa del centro de los texeles del borde
+    margin_v: f32,
+}
+
+impl AtlasTile {
+    // Tesela con los límites en píxeles (origen arriba a la izquierda, como en la imagen)
+    fn from_pixels(x: u32, y: u32, width: u32, height: u32, atlas_width: u32, atlas_height: u32) -> Self {
+        let (atlas_width, atlas_height) = (atlas_width as f32, atlas_height as f32);
+        AtlasTile {
+            u_min: x as f32 / atlas_width,
+            v_min: 1.0 - (y + height) as f32 / atlas_height,
+            u_max: (x + width) as f32 / atlas_width,
+            v_max: 1.0 - y as f32 / atlas_height,
+            margin_u: 0.5 / atlas_width,
+            margin_v: 0.5 / atlas_height,
+        }
+    }
+
+    // Lado medio de la tesela en UV del atlas
+    pub fn extent(&self) -> f32 {
+        ((self.u_max - self.u_min) * (self.v_max - self.v_min)).sqrt()
+    }
+
+    // Lleva una UV de 0 a 1 sobre la tesela a la UV del atlas, sin salirse hacia las vecinas
+    pub fn remap(&self, u: f32, v: f32) -> (f32, f32) {
+        let u = self.u_min + u.clamp(0.0, 1.0) * (self.u_max - self.u_min);
+        let v = self.v_min + v.clamp(0.0, 1.0) * (self.v_max - self.v_min);
+        (
+            u.clamp(self.u_min + self.margin_u, self.u_max - self.margin_u),
+            v.clamp(self.v_min + self.margin_v, self.v_max - self.margin_v),
+        )
+    }
+}
+
+// Muchas texturas de bloque en una sola imagen, cada una con su nombre
+#[derive(Debug, Clone)]
+pub struct TextureAtlas {
+    pub texture: Arc<Texture>,
+    tiles: HashMap<String, AtlasTile>,
+}
+
+impl TextureAtlas {
+    // Empaqueta imágenes sueltas por filas (de la más alta a la más baja). Cada tesela se rodea con
+    // `padding` píxeles que repiten su borde, para que el filtrado no mezcle colores de las vecinas
+    pub fn pack(entries: &[(&str, &str)], padding: u32) -> Self {
+        let mut images: Vec<(&str, DynamicImage)> = entries
+            .iter()
+            .map(|(name, path)| {
+                let img = ImageReader::open(path)
+                    .expect("Failed to open file")
+                    .decode()
+                    .expect("Failed to decode image");
+                (*name, img)
+            })
+            .collect();
+        assert!(!images.is_empty(), "Texture atlas needs at least one tile");
+        images.sort_by_key(|(_, img)| std::cmp::Reverse(img.height()));
+
+        // Ancho cuadrado aproximado, sin bajar del ancho de la tesela más ancha
+        let area: u32 = images
+            .iter()
+            .map(|(_, img)| (img.width() + 2 * padding) * (img.height() + 2 * padding))
+            .sum();
+        let widest = images.iter().map(|(_, img)| img.width() + 2 * padding).max().unwrap();
+        let atlas_width = ((area as f32).sqrt().ceil() as u32).max(widest).next_power_of_two();
+
+        // Posición de cada tesela (sin contar el relleno)
+        let mut placements: Vec<(u32, u32)> = Vec::with_capacity(images.len());
+        let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+        for (_, img) in &images {
+            let (width, height) = (img.width() + 2 * padding, img.height() + 2 * padding);
+            if x + width > atlas_width {
+                x = 0;
+                y += shelf_height;
+                shelf_height = 0;
+            }
+            placements.push((x + padding, y + padding));
+            x += width;
+            shelf_height = shelf_height.max(height);
+        }
+        let atlas_height = y + shelf_height;
+
+        let mut atlas = RgbaImage::new(atlas_width, atlas_height);
+        let mut tiles = HashMap::new();
+        for ((name, img), &(tile_x, tile_y)) in images.iter().zip(&placements) {
+            let (width, height) = img.dimensions();
+            let padding = padding as i64;
+            for py in -padding..height as i64 + padding {
+                for px in -padding..width as i64 + padding {
+                    let sx = px.clamp(0, width as i64 - 1) as u32;
+                    let sy = py.clamp(0, height as i64 - 1) as u32;
+                    let dx = (tile_x as i64 + px) as u32;
+                    let dy = (tile_y as i64 + py) as u32;
+                    atlas.put_pixel(dx, dy, img.get_pixel(sx, sy));
+                }
+            }
+            tiles.insert(
+                name.to_string(),
+                AtlasTile::from_pixels(tile_x, tile_y, width, height, atlas_width, atlas_height),
+            );
+        }
+
+        let id = format!("atlas:{}", entries.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(","));
+        TextureAtlas {
+            texture: Arc::new(Texture::from_image(&id, DynamicImage::ImageRgba8(atlas))),
+            tiles,
+        }
+    }
+
+    // Atlas ya armado como rejilla de teselas cuadradas (al estilo de terrain.png). Los nombres van
+    // por filas de izquierda a derecha; un nombre vacío deja esa celda sin usar
+    pub fn from_grid(file_path: &str, tile_size: u32, names: &[&str]) -> Self {
+        let texture = Arc::new(Texture::new(file_path));
+        let (atlas_width, atlas_height) = (texture.width as u32, texture.height as u32);
+        let columns = atlas_width / tile_size;
+        let rows = atlas_height / tile_size;
+        assert!(
+            names.len() as u32 <= columns * rows,
+            "More tile names than cells in the atlas grid"
+        );
+
+        let mut tiles = HashMap::new();
+        for (index, name) in names.iter().enumerate() {
+            if name.is_empty() {
+                continue;
+            }
+            let x = index as u32 % columns * tile_size;
+            let y = index as u32 / columns * tile_size;
+            tiles.insert(
+                name.to_string(),
+                AtlasTile::from_pixels(x, y, tile_size, tile_size, atlas_width, atlas_height),
+            );
+        }
+        TextureAtlas { texture, tiles }
+    }
+
+    pub fn get(&self, name: &str) -> Option<AtlasTile> {
+        self.tiles.get(name).copied()
+    }
+
+    pub fn tile(&self, name: &str) -> AtlasTile {
+        self.get(name)
+            .unwrap_or_else(|| panic!("Unknown atlas tile: {}", name))
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.tiles.keys().map(|name| name.as_str())
+    }
+}
@@ -0,0 +1,137 @@
+extern crate image;
+use crate::color::Color;
+use crate::pbr::{color_to_vec, vec_to_color};
+use image::{GenericImageView, ImageReader};
+
+// Índices de tinte (el `tintindex` de los modelos de Minecraft) y su lugar en `BiomeColors::tints`
+pub const TINT_GRASS: usize = 0;
+#[allow(dead_code)]
+pub const TINT_FOLIAGE: usize = 1;
+#[allow(dead_code)]
+pub const TINT_WATER: usize = 2;
+
+// Mapa de color de bioma (colormap/grass.png, colormap/foliage.png): x crece al bajar la
+// temperatura e y al bajar la humedad; solo se usa el triángulo inferior izquierdo
+#[derive(Debug, Clone)]
+pub struct Colormap {
+    pub width: usize,
+    pub height: usize,
+    texels: Vec<Color>,
+}
+
+impl Colormap {
+    #[allow(dead_code)]
+    pub fn new(file_path: &str) -> Self {
+        let img = ImageReader::open(file_path)
+            .expect("Failed to open file")
+            .decode()
+            .expect("Failed to decode image");
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        let texels = img
+            .pixels()
+            .map(|(_, _, pixel)| Color::new(pixel[0], pixel[1], pixel[2]))
+            .collect();
+        Colormap { width, height, texels }
+    }
+
+    // Mapa de 256x256 interpolando las tres esquinas del triángulo: caluroso y húmedo (`lush`),
+    // caluroso y seco (`dry`) y frío (`cold`), para cuando no hay un paquete de recursos a mano
+    pub fn from_corners(lush: Color, dry: Color, cold: Color) -> Self {
+        let size = 256;
+        let (lush, dry, cold) = (color_to_vec(lush), color_to_vec(dry), color_to_vec(cold));
+        let mut texels = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let temperature = 1.0 - x as f32 / 255.0;
+                let humidity = (1.0 - y as f32 / 255.0).min(temperature);
+                let color = lush * humidity + dry * (temperature - humidity) + cold * (1.0 - temperature);
+                texels.push(vec_to_color(&color));
+            }
+        }
+        Colormap {
+            width: size,
+            height: size,
+            texels,
+        }
+    }
+
+    // Esquinas del colormap de pasto de Minecraft
+    pub fn grass() -> Self {
+        Colormap::from_corners(Color::from_hex(0x47CD33), Color::from_hex(0xBFB755), Color::from_hex(0x80B497))
+    }
+
+    // Esquinas del colormap de hojas de Minecraft
+    pub fn foliage() -> Self {
+        Colormap::from_corners(Color::from_hex(0x1ABF00), Color::from_hex(0xAEA42A), Color::from_hex(0x60A17B))
+    }
+
+    // Igual que Minecraft: la humedad se escala por la temperatura, así que nunca sale del triángulo
+    pub fn lookup(&self, temperature: f32, humidity: f32) -> Color {
+        let temperature = temperature.clamp(0.0, 1.0);
+        let humidity = humidity.clamp(0.0, 1.0) * temperature;
+        let x = ((1.0 - temperature) * (self.width - 1) as f32) as usize;
+        let y = ((1.0 - humidity) * (self.height - 1) as f32) as usize;
+        self.texels[y * self.width + x]
+    }
+}
+
+// Clima de un bioma; el color del agua no sale de un colormap sino que es fijo por bioma
+#[derive(Debug, Clone, Copy)]
+pub struct Biome {
+    pub temperature: f32,
+    pub humidity: f32,
+    pub water: Color,
+}
+
+impl Biome {
+    pub fn new(temperature: f32, humidity: f32, water: Color) -> Self {
+        Biome {
+            temperature,
+            humidity,
+            water,
+        }
+    }
+
+    // Algunos biomas de Minecraft por su nombre (p. ej. "plains", "jungle")
+    pub fn named(name: &str) -> Option<Biome> {
+        let water = Color::from_hex(0x3F76E4);
+        let biome = match name {
+            "plains" => Biome::new(0.8, 0.4, water),
+            "forest" => Biome::new(0.7, 0.8, water),
+            "jungle" => Biome::new(0.95, 0.9, water),
+            "desert" => Biome::new(2.0, 0.0, water),
+            "savanna" => Biome::new(1.2, 0.0, water),
+            "taiga" => Biome::new(0.25, 0.8, water),
+            "snowy_plains" => Biome::new(0.0, 0.5, Color::from_hex(0x3D57D6)),
+            _ => return None,
+        };
+        Some(biome)
+    }
+}
+
+// Colormaps con los que se tiñen el pasto y las hojas
+#[derive(Debug, Clone)]
+pub struct BiomeColors {
+    pub grass: Colormap,
+    pub foliage: Colormap,
+}
+
+impl BiomeColors {
+    pub fn new(grass: Colormap, foliage: Colormap) -> Self {
+        BiomeColors { grass, foliage }
+    }
+
+    // Colormaps aproximados a partir de las esquinas de los de Minecraft
+    pub fn vanilla() -> Self {
+        BiomeColors::new(Colormap::grass(), Colormap::foliage())
+    }
+
+    // Color de cada índice de tinte en el bioma (ver TINT_GRASS, TINT_FOLIAGE y TINT_WATER)
+    pub fn tints(&self, biome: &Biome) -> Vec<Color> {
+        vec![
+            self.grass.lookup(biome.temperature, biome.humidity),
+            self.foliage.lookup(biome.temperature, biome.humidity),
+            biome.water,
+        ]
+    }
+}
@@ -0,0 +1,258 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+const SAH_BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 2;
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+
+#[derive(Debug, Clone, Copy)]
+struct BvhNode {
+    bounds: Aabb,
+    // Si `count` > 0 es una hoja y `first` apunta al primer objeto;
+    // si no, `first` es el índice del hijo izquierdo (el derecho va justo después)
+    first: usize,
+    count: usize,
+}
+
+#[derive(Clone, Copy)]
+struct Bin {
+    bounds: Aabb,
+    count: usize,
+}
+
+pub struct Bvh<T> {
+    objects: Vec<T>,
+    nodes: Vec<BvhNode>,
+}
+
+impl<T: RayIntersect> Bvh<T> {
+    pub fn new(objects: Vec<T>) -> Self {
+        let mut bvh = Bvh {
+            objects: Vec::new(),
+            nodes: Vec::new(),
+        };
+        if objects.is_empty() {
+            return bvh;
+        }
+
+        let boxes: Vec<Aabb> = objects.iter().map(|o| o.bounding_box()).collect();
+        let centroids: Vec<Vec3> = boxes.iter().map(|b| b.centroid()).collect();
+        let mut indices: Vec<usize> = (0..objects.len()).collect();
+
+        bvh.nodes.reserve(2 * objects.len());
+        bvh.nodes.push(BvhNode {
+            bounds: Aabb::empty(),
+            first: 0,
+            count: objects.len(),
+        });
+        bvh.subdivide(0, &mut indices, &boxes, &centroids);
+
+        // Reordenar los objetos para que cada hoja sea un rango contiguo
+        let mut slots: Vec<Option<T>> = objects.into_iter().map(Some).collect();
+        bvh.objects = indices
+            .iter()
+            .map(|&i| slots[i].take().expect("índice duplicado en el BVH"))
+            .collect();
+        bvh
+    }
+
+    fn subdivide(&mut self, node_index: usize, indices: &mut [usize], boxes: &[Aabb], centroids: &[Vec3]) {
+        let first = self.nodes[node_index].first;
+        let count = self.nodes[node_index].count;
+        let range = &mut indices[first..first + count];
+
+        let mut bounds = Aabb::empty();
+        let mut centroid_bounds = Aabb::empty();
+        for &i in range.iter() {
+            bounds = bounds.union(&boxes[i]);
+            centroid_bounds.grow(&centroids[i]);
+        }
+        self.nodes[node_index].bounds = bounds;
+
+        if count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        // Buscar el mejor corte con la heurística de área de superficie (SAH) usando bins
+        let mut best: Option<(usize, usize, f32)> = None;
+        for axis in 0..3 {
+            let lo = centroid_bounds.min[axis];
+            let hi = centroid_bounds.max[axis];
+            if hi - lo <= f32::EPSILON {
+                continue;
+            }
+            let scale = SAH_BINS as f32 / (hi - lo);
+            let bin_of = |c: f32| (((c - lo) * scale) as usize).min(SAH_BINS - 1);
+
+            let mut bins = [Bin { bounds: Aabb::empty(), count: 0 }; SAH_BINS];
+            for &i in range.iter() {
+                let bin = &mut bins[bin_of(centroids[i][axis])];
+                bin.count += 1;
+                bin.bounds = bin.bounds.union(&boxes[i]);
+            }
+
+            // Barridos de izquierda a derecha y de derecha a izquierda
+            let mut left_area = [0.0; SAH_BINS - 1];
+            let mut left_count = [0; SAH_BINS - 1];
+            let mut acc = Aabb::empty();
+            let mut acc_count = 0;
+            for b in 0..SAH_BINS - 1 {
+                acc = acc.union(&bins[b].bounds);
+                acc_count += bins[b].count;
+                left_area[b] = acc.surface_area();
+                left_count[b] = acc_count;
+            }
+
+            let mut acc = Aabb::empty();
+            let mut acc_count = 0;
+            for b in (1..SAH_BINS).rev() {
+                acc = acc.union(&bins[b].bounds);
+                acc_count += bins[b].count;
+                let cost = left_count[b - 1] as f32 * left_area[b - 1] + acc_count as f32 * acc.surface_area();
+                if best.is_none_or(|(_, _, best_cost)| cost < best_cost) {
+                    best = Some((axis, b, cost));
+                }
+            }
+        }
+
+        let parent_area = bounds.surface_area();
+        let leaf_cost = count as f32 * INTERSECTION_COST;
+        let (axis, split_bin, cost) = match best {
+            Some(split) => split,
+            None => return, // Todos los centroides coinciden
+        };
+        let split_cost = if parent_area > 0.0 {
+            TRAVERSAL_COST + INTERSECTION_COST * cost / parent_area
+        } else {
+            leaf_cost
+        };
+        if split_cost >= leaf_cost {
+            return;
+        }
+
+        // Particionar los índices según el bin elegido
+        let lo = centroid_bounds.min[axis];
+        let scale = SAH_BINS as f32 / (centroid_bounds.max[axis] - lo);
+        let mut left = 0;
+        for i in 0..count {
+            let bin = (((centroids[range[i]][axis] - lo) * scale) as usize).min(SAH_BINS - 1);
+            if bin < split_bin {
+                range.swap(i, left);
+                left += 1;
+            }
+        }
+        if left == 0 || left == count {
+            return;
+        }
+
+        let left_index = self.nodes.len();
+        self.nodes.push(BvhNode { bounds: Aabb::empty(), first, count: left });
+        self.nodes.push(BvhNode { bounds: Aabb::empty(), first: first + left, count: count - left });
+        self.nodes[node_index].first = left_index;
+        self.nodes[node_index].count = 0;
+
+        self.subdivide(left_index, indices, boxes, centroids);
+        self.subdivide(left_index + 1, indices, boxes, centroids);
+    }
+
+    pub fn objects(&self) -> &[T] {
+        &self.objects
+    }
+}
+
+impl<T: RayIntersect> Bvh<T> {
+    // Recorre los nodos que el rayo toca, del más cercano al más lejano.
+    // `visit` prueba los objetos de una hoja y puede acortar el rango del rayo;
+    // si devuelve true el recorrido termina
+    fn traverse<'s>(&'s self, ray: &Ray, mut visit: impl FnMut(&'s [T], &mut Ray) -> bool) {
+        if self.nodes.is_empty() {
+            return;
+        }
+
+        let mut ray = *ray;
+        let inv_dir = ray.inv_direction();
+        if self.nodes[0].bounds.hit(&ray, &inv_dir).is_none() {
+            return;
+        }
+
+        let mut stack: Vec<usize> = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+
+            if node.count > 0 {
+                if visit(&self.objects[node.first..node.first + node.count], &mut ray) {
+                    return;
+                }
+                continue;
+            }
+
+            // Visitar primero el hijo más cercano para podar antes el resto
+            let left = node.first;
+            let right = node.first + 1;
+            let hit_left = self.nodes[left].bounds.hit(&ray, &inv_dir);
+            let hit_right = self.nodes[right].bounds.hit(&ray, &inv_dir);
+
+            match (hit_left, hit_right) {
+                (Some(tl), Some(tr)) => {
+                    if tl <= tr {
+                        stack.push(right);
+                        stack.push(left);
+                    } else {
+                        stack.push(left);
+                        stack.push(right);
+                    }
+                }
+                (Some(_), None) => stack.push(left),
+                (None, Some(_)) => stack.push(right),
+                (None, None) => {}
+            }
+        }
+    }
+}
+
+impl<T: RayIntersect> RayIntersect for Bvh<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let mut intersect = Intersect::empty();
+        self.traverse(ray, |objects, ray| {
+            for object in objects {
+                let i = object.ray_intersect(ray);
+                if i.is_intersecting && i.distance < ray.t_max {
+                    ray.t_max = i.distance;
+                    intersect = i;
+                }
+            }
+            false
+        });
+        intersect
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        // Un bloqueador opaco corta el recorrido; uno traslúcido se guarda por si no aparece otro
+        let mut occluder = None;
+        self.traverse(ray, |objects, ray| {
+            for object in objects {
+                if let Some(material) = object.occluder(ray) {
+                    occluder = Some(material);
+                    if material.is_opaque() {
+                        return true;
+                    }
+                }
+            }
+            false
+        });
+        occluder
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        match self.nodes.first() {
+            Some(root) => root.bounds,
+            None => Aabb::empty(),
+        }
+    }
+}
@@ -6,6 +6,7 @@ pub struct Camera {
     pub center: Vec3, 
     pub up: Vec3,
     pub has_changed: bool, 
+    pub shutter_samples: u32, // Muestras por píxel repartidas en el obturador (desenfoque de movimiento)
 }
 
 impl Camera {
@@ -15,6 +16,7 @@ impl Camera {
             center,
             up,
             has_changed: true,
+            shutter_samples: 1,
         }
     }
 
@@ -27,6 +27,7 @@ impl Color {
     }
 
     // Function to return the color as a hex value
+    #[allow(clippy::wrong_self_convention)]
     pub const fn to_hex(&self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
@@ -0,0 +1,201 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect, Span};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference, // left - right
+}
+
+impl CsgOperation {
+    fn inside(&self, in_left: bool, in_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right,
+        }
+    }
+}
+
+// Combina dos objetos a partir de los tramos del rayo que quedan dentro de cada uno
+pub struct Csg {
+    pub operation: CsgOperation,
+    pub left: Box<dyn RayIntersect + Send + Sync>,
+    pub right: Box<dyn RayIntersect + Send + Sync>,
+}
+
+struct Event<'a> {
+    hit: Intersect<'a>,
+    entering: bool,
+    from_left: bool,
+}
+
+impl Csg {
+    pub fn new(
+        operation: CsgOperation,
+        left: Box<dyn RayIntersect + Send + Sync>,
+        right: Box<dyn RayIntersect + Send + Sync>,
+    ) -> Self {
+        Csg {
+            operation,
+            left,
+            right,
+        }
+    }
+
+    // Primer borde del resultado dentro del rango del rayo (con la normal exterior)
+    fn first_boundary(&self, ray: &Ray) -> Option<Intersect<'_>> {
+        self.spans(ray)
+            .into_iter()
+            .flat_map(|span| [span.enter, span.exit])
+            .find(|hit| hit.is_intersecting && ray.in_range(hit.distance))
+    }
+}
+
+impl RayIntersect for Csg {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.first_boundary(ray) {
+            Some(mut intersect) => {
+                let normal = intersect.normal;
+                intersect.set_face_normal(&ray.direction, &normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.first_boundary(ray).map(|hit| hit.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let left = self.left.bounding_box();
+        let right = self.right.bounding_box();
+        match self.operation {
+            CsgOperation::Union => left.union(&right),
+            CsgOperation::Intersection => {
+                let min = left.min.sup(&right.min);
+                let max = left.max.inf(&right.max);
+                // Si los hijos no se tocan la intersección queda vacía: se devuelve una caja de volumen
+                // cero en vez de una invertida (min > max), que el BVH y Aabb::hit no esperan
+                if (0..3).any(|axis| min[axis] > max[axis]) {
+                    let corner = min.inf(&max);
+                    Aabb::new(corner, corner)
+                } else {
+                    Aabb::new(min, max)
+                }
+            }
+            CsgOperation::Difference => left,
+        }
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let mut events: Vec<Event> = Vec::new();
+        for (spans, from_left) in [(self.left.spans(ray), true), (self.right.spans(ray), false)] {
+            for span in spans {
+                events.push(Event { hit: span.enter, entering: true, from_left });
+                events.push(Event { hit: span.exit, entering: false, from_left });
+            }
+        }
+
+        // En empates, la unión procesa primero las entradas para no dejar caras internas
+        // entre piezas que se tocan; las demás operaciones procesan primero las salidas
+        let enters_first = self.operation == CsgOperation::Union;
+        events.sort_by(|a, b| {
+            a.hit
+                .distance
+                .partial_cmp(&b.hit.distance)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| (a.entering != enters_first).cmp(&(b.entering != enters_first)))
+        });
+
+        let mut spans = Vec::new();
+        let mut in_left = false;
+        let mut in_right = false;
+        let mut enter: Option<Intersect> = None;
+
+        for mut event in events {
+            let was_inside = self.operation.inside(in_left, in_right);
+            if event.from_left {
+                in_left = event.entering;
+            } else {
+                in_right = event.entering;
+            }
+            let is_inside = self.operation.inside(in_left, in_right);
+
+            // En la diferencia, las caras del objeto derecho miran hacia adentro de él
+            if self.operation == CsgOperation::Difference && !event.from_left {
+                event.hit.normal = -event.hit.normal;
+            }
+
+            if !was_inside && is_inside {
+                enter = Some(event.hit);
+            } else if was_inside && !is_inside {
+                if let Some(start) = enter.take() {
+                    spans.push(Span { enter: start, exit: event.hit });
+                }
+            }
+        }
+
+        spans
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+    use crate::cube::Cube;
+    use nalgebra_glm::Vec3;
+
+    fn cube(min: [f32; 3], max: [f32; 3]) -> Box<dyn RayIntersect + Send + Sync> {
+        Box::new(Cube {
+            min: Vec3::new(min[0], min[1], min[2]),
+            max: Vec3::new(max[0], max[1], max[2]),
+            material: Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black()),
+            motion: None,
+        })
+    }
+
+    #[test]
+    fn difference_splits_spans_at_the_hole() {
+        // Bloque unitario con una ranura de x = 0.25 a x = 0.75 que lo atraviesa
+        let csg = Csg::new(
+            CsgOperation::Difference,
+            cube([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]),
+            cube([0.25, -1.0, -1.0], [0.75, 2.0, 2.0]),
+        );
+        let ray = Ray::new(Vec3::new(-1.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let spans = csg.spans(&ray);
+        let bounds: Vec<(f32, f32)> = spans.iter().map(|span| (span.enter.distance, span.exit.distance)).collect();
+        assert_eq!(bounds, vec![(1.0, 1.25), (1.75, 2.0)]);
+        // Las caras de la ranura miran hacia afuera del resultado, es decir, hacia la ranura
+        assert_eq!(spans[0].exit.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(spans[1].enter.normal, Vec3::new(-1.0, 0.0, 0.0));
+
+        // Desde dentro de la ranura, lo primero es la pared del otro lado, vista de frente
+        let inside = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        let hit = csg.ray_intersect(&inside);
+        assert!(hit.is_intersecting && hit.front_face);
+        assert!((hit.distance - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn disjoint_intersection_has_an_empty_box() {
+        let csg = Csg::new(
+            CsgOperation::Intersection,
+            cube([0.0, 0.0, 0.0], [1.0, 1.0, 1.0]),
+            cube([2.0, 0.5, 0.0], [3.0, 2.0, 1.0]),
+        );
+        let bounds = csg.bounding_box();
+        for axis in 0..3 {
+            assert!(bounds.min[axis] <= bounds.max[axis]);
+        }
+        assert_eq!(bounds.surface_area(), 0.0);
+        let ray = Ray::new(Vec3::new(-1.0, 0.75, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(!csg.ray_intersect(&ray).is_intersecting);
+    }
+}
@@ -1,114 +1,306 @@
-use crate::material::Material;
-use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::aabb::Aabb;
+use crate::biome::{Biome, BiomeColors};
+use crate::material::{Material, UvMapping};
+use crate::motion::Motion;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect, Span};
+use crate::transform::Frame;
 use nalgebra_glm::Vec3;
 
 pub struct Cube {
-    pub min: Vec3,          // La esquina mínima del cubo
-    pub max: Vec3,          // La esquina máxima del cubo
-    pub material: Material, // Material del cubo (incluyendo albedo)
+    pub min: Vec3,              // La esquina mínima del cubo
+    pub max: Vec3,              // La esquina máxima del cubo
+    pub material: Material,     // Material del cubo (incluyendo albedo)
+    pub motion: Option<Motion>, // Movimiento durante el obturador; min/max son la posición en reposo
 }
 
 impl Cube {
-    pub fn get_uv(&self, point: &Vec3, normal: &Vec3) -> (f32, f32) {
-        let size = self.max - self.min;
-        let local_point = point - self.min;
-
-        let img_width = 375.0;
-        let img_height = 500.0;
-        let num_columns = 3.0;
-        let num_rows = 4.0;
-
-        let column_width = img_width / num_columns;
-        let row_height = img_height / num_rows;
-
-        let mut u = 0.0;
-        let mut v = 0.0;
-
-        let (u, v) = if normal.x > 0.0 {
-            // Cara derecha (Face 6)
-            (
-                (local_point.y / size.y) * column_width + 2.0 * column_width, // Columna 3
-                (1.0 - local_point.z / size.z) * row_height + row_height,
-            ) // Fila 2
-        } else if normal.x < 0.0 {
-            // Cara izquierda (Face 4)
-            (
-                (1.0 - local_point.y / size.y) * column_width, // Columna 1
-                (1.0 - local_point.z / size.z) * row_height + row_height,
-            ) // Fila 2
-        } else if normal.y > 0.0 {
-            // Cara superior (Face 11)
-            (
-                (local_point.x / size.x) * column_width + column_width, // Columna 2
-                (local_point.z / size.z) * row_height + 3.0 * row_height,
-            ) // Fila 4
-        } else if normal.y < 0.0 {
-            // Cara inferior (Face 5)
-            (
-                (local_point.x / size.x) * column_width + column_width, // Columna 2
-                (local_point.z / size.z) * row_height + row_height,
-            ) // Fila 2
+    // Tiñe las caras con índice de tinte de su material con los colores del bioma
+    pub fn with_biome(mut self, colors: &BiomeColors, biome: &Biome) -> Self {
+        self.material = self.material.with_tints(colors.tints(biome));
+        self
+    }
+}
+
+// Caras de un bloque con los nombres de Minecraft: norte = -Z, sur = +Z, este = +X, oeste = -X
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Face {
+    Up,
+    Down,
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Face {
+    pub fn from_normal(normal: &Vec3) -> Face {
+        let abs = normal.abs();
+        if abs.x >= abs.y && abs.x >= abs.z {
+            if normal.x > 0.0 { Face::East } else { Face::West }
+        } else if abs.y >= abs.z {
+            if normal.y > 0.0 { Face::Up } else { Face::Down }
         } else if normal.z > 0.0 {
-            // Cara frontal (Face 2)
-            (
-                (local_point.x / size.x) * column_width + column_width, // Columna 2
-                (1.0 - local_point.y / size.y) * row_height,
-            ) // Fila 1
+            Face::South
         } else {
-            // Cara trasera (Face 8)
-            (
-                (local_point.x / size.x) * column_width + column_width, // Columna 2
-                (local_point.y / size.y) * row_height + 2.0 * row_height,
-            ) // Fila 3
-        };
-
-        // Convertir coordenadas de píxeles a coordenadas UV dividiendo por las dimensiones de la imagen
-        (u / img_width, 1.0 - v / img_height)
+            Face::North
+        }
+    }
+
+    // Direcciones en que crecen u y v con la UV de block_face_uv (v hacia arriba en las caras laterales)
+    pub fn tangents(&self) -> (Vec3, Vec3) {
+        match self {
+            Face::Up => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0)),
+            Face::Down => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0)),
+            Face::North => (Vec3::new(-1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            Face::South => (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0)),
+            Face::East => (Vec3::new(0.0, 0.0, -1.0), Vec3::new(0.0, 1.0, 0.0)),
+            Face::West => (Vec3::new(0.0, 0.0, 1.0), Vec3::new(0.0, 1.0, 0.0)),
+        }
+    }
+}
+
+// UV de 0 a 1 sobre cada cara, orientada como en Minecraft: en las caras laterales la parte
+// de arriba de la textura queda arriba, y en la cara superior apunta al norte
+pub fn block_face_uv(min: &Vec3, max: &Vec3, point: &Vec3, face: Face) -> (f32, f32) {
+    let local = (point - min).component_div(&(max - min));
+    match face {
+        Face::Up => (local.x, 1.0 - local.z),
+        Face::Down => (local.x, local.z),
+        Face::North => (1.0 - local.x, local.y),
+        Face::South => (local.x, local.y),
+        Face::East => (1.0 - local.z, local.y),
+        Face::West => (local.z, local.y),
+    }
+}
+
+pub fn box_uv(mapping: UvMapping, min: &Vec3, max: &Vec3, point: &Vec3, normal: &Vec3) -> (f32, f32) {
+    match mapping {
+        UvMapping::Cross => face_uv(min, max, point, normal),
+        UvMapping::PerFace => block_face_uv(min, max, point, Face::from_normal(normal)),
+    }
+}
+
+// Impacto sobre la cara de una caja alineada (o de la celda `min..max` que la contiene), con la UV,
+// el marco tangente y la cara según el modo de mapeo del material. La normal es la exterior
+pub fn box_face_hit<'a>(
+    min: &Vec3,
+    max: &Vec3,
+    point: Vec3,
+    normal: Vec3,
+    t: f32,
+    material: &'a Material,
+) -> Intersect<'a> {
+    let face = Face::from_normal(&normal);
+    let mapping = material.uv_mapping();
+    let (u, v) = box_uv(mapping, min, max, &point, &normal);
+    let (tangent, bitangent) = match mapping {
+        UvMapping::Cross => face_tangents(&normal),
+        UvMapping::PerFace => face.tangents(),
+    };
+
+    // En la cruz cada cara ocupa un tercio del ancho y un cuarto del alto de la imagen
+    let size = max - min;
+    let axis = normal.iamax();
+    let extent = (size[(axis + 1) % 3] * size[(axis + 2) % 3]).abs().sqrt();
+    let uv_density = match mapping {
+        UvMapping::Cross => (1.0f32 / 12.0).sqrt() / extent,
+        UvMapping::PerFace => 1.0 / extent,
+    };
+
+    Intersect::new(point, normal, t, material, u, v)
+        .with_tangents(tangent, bitangent)
+        .with_face(face)
+        .with_uv_density(uv_density)
+}
+
+// Coordenadas UV en la textura en cruz (3x4) para un punto sobre la cara de una caja alineada a los ejes
+pub fn face_uv(min: &Vec3, max: &Vec3, point: &Vec3, normal: &Vec3) -> (f32, f32) {
+    let size = max - min;
+    let local_point = point - min;
+
+    let img_width = 375.0;
+    let img_height = 500.0;
+    let num_columns = 3.0;
+    let num_rows = 4.0;
+
+    let column_width = img_width / num_columns;
+    let row_height = img_height / num_rows;
+
+    let (u, v) = if normal.x > 0.0 {
+        // Cara derecha (Face 6)
+        (
+            (local_point.y / size.y) * column_width + 2.0 * column_width, // Columna 3
+            (1.0 - local_point.z / size.z) * row_height + row_height,
+        ) // Fila 2
+    } else if normal.x < 0.0 {
+        // Cara izquierda (Face 4)
+        (
+            (1.0 - local_point.y / size.y) * column_width, // Columna 1
+            (1.0 - local_point.z / size.z) * row_height + row_height,
+        ) // Fila 2
+    } else if normal.y > 0.0 {
+        // Cara superior (Face 11)
+        (
+            (local_point.x / size.x) * column_width + column_width, // Columna 2
+            (local_point.z / size.z) * row_height + 3.0 * row_height,
+        ) // Fila 4
+    } else if normal.y < 0.0 {
+        // Cara inferior (Face 5)
+        (
+            (local_point.x / size.x) * column_width + column_width, // Columna 2
+            (local_point.z / size.z) * row_height + row_height,
+        ) // Fila 2
+    } else if normal.z > 0.0 {
+        // Cara frontal (Face 2)
+        (
+            (local_point.x / size.x) * column_width + column_width, // Columna 2
+            (1.0 - local_point.y / size.y) * row_height,
+        ) // Fila 1
+    } else {
+        // Cara trasera (Face 8)
+        (
+            (local_point.x / size.x) * column_width + column_width, // Columna 2
+            (local_point.y / size.y) * row_height + 2.0 * row_height,
+        ) // Fila 3
+    };
+
+    // Convertir coordenadas de píxeles a coordenadas UV dividiendo por las dimensiones de la imagen
+    (u / img_width, 1.0 - v / img_height)
+}
+
+// Distancias de entrada y salida de la recta del rayo a través de la caja, sin recortar al rango
+pub fn slab_range(min: &Vec3, max: &Vec3, ray: &Ray) -> Option<(f32, f32)> {
+    // Calcular el inverso de cada componente del vector de dirección
+    let inv_dir = ray.inv_direction();
+
+    let tmin = (min - ray.origin).component_mul(&inv_dir);
+    let tmax = (max - ray.origin).component_mul(&inv_dir);
+
+    let t1 = tmin[0].min(tmax[0]);
+    let t2 = tmin[0].max(tmax[0]);
+    let t3 = tmin[1].min(tmax[1]);
+    let t4 = tmin[1].max(tmax[1]);
+    let t5 = tmin[2].min(tmax[2]);
+    let t6 = tmin[2].max(tmax[2]);
+
+    let t_enter = t1.max(t3).max(t5);
+    let t_exit = t2.min(t4).min(t6);
+
+    if t_enter < t_exit {
+        Some((t_enter, t_exit))
+    } else {
+        None
+    }
+}
+
+// Distancia a la primera cara de la caja que el rayo cruza dentro de su rango
+pub fn box_hit_distance(min: &Vec3, max: &Vec3, ray: &Ray) -> Option<f32> {
+    let (t_enter, t_exit) = slab_range(min, max, ray)?;
+
+    // Si el rayo nace dentro de la caja, la superficie que encuentra es la de salida
+    if ray.in_range(t_enter) {
+        Some(t_enter)
+    } else if t_enter <= ray.t_min && ray.in_range(t_exit) {
+        Some(t_exit)
+    } else {
+        None
+    }
+}
+
+// Direcciones en que crecen u y v sobre cada cara, siguiendo la distribución en cruz de face_uv
+pub fn face_tangents(normal: &Vec3) -> (Vec3, Vec3) {
+    if normal.x > 0.0 {
+        (Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+    } else if normal.x < 0.0 {
+        (Vec3::new(0.0, -1.0, 0.0), Vec3::new(0.0, 0.0, 1.0))
+    } else if normal.y != 0.0 {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0))
+    } else if normal.z > 0.0 {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0))
+    } else {
+        (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, -1.0, 0.0))
+    }
+}
+
+// Normal exterior de la cara de la caja sobre la que está `point`
+pub fn box_normal(min: &Vec3, max: &Vec3, point: &Vec3) -> Vec3 {
+    if (point[0] - min[0]).abs() < 1e-3 {
+        Vec3::new(-1.0, 0.0, 0.0)
+    } else if (point[0] - max[0]).abs() < 1e-3 {
+        Vec3::new(1.0, 0.0, 0.0)
+    } else if (point[1] - min[1]).abs() < 1e-3 {
+        Vec3::new(0.0, -1.0, 0.0)
+    } else if (point[1] - max[1]).abs() < 1e-3 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else if (point[2] - min[2]).abs() < 1e-3 {
+        Vec3::new(0.0, 0.0, -1.0)
+    } else {
+        Vec3::new(0.0, 0.0, 1.0)
+    }
+}
+
+impl Cube {
+    // Impacto sobre la superficie a distancia `t`, con la normal exterior de la cara
+    fn surface_hit(&self, ray: &Ray, t: f32) -> Intersect<'_> {
+        let point = ray.at(t);
+        let normal = box_normal(&self.min, &self.max, &point);
+        box_face_hit(&self.min, &self.max, point, normal, t, &self.material)
+    }
+
+    // Marco del cubo en el instante del rayo, si se está moviendo
+    fn frame(&self, ray: &Ray) -> Option<Frame> {
+        let center = (self.min + self.max) * 0.5;
+        self.motion.map(|motion| motion.frame_at(ray.time, &center))
     }
 }
 
 impl RayIntersect for Cube {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
-        // Calcular el inverso de cada componente del vector de dirección
-        let inv_dir = Vec3::new(
-            1.0 / ray_direction[0],
-            1.0 / ray_direction[1],
-            1.0 / ray_direction[2],
-        );
-
-        let tmin = (self.min - ray_origin).component_mul(&inv_dir);
-        let tmax = (self.max - ray_origin).component_mul(&inv_dir);
-
-        let t1 = tmin[0].min(tmax[0]);
-        let t2 = tmin[0].max(tmax[0]);
-        let t3 = tmin[1].min(tmax[1]);
-        let t4 = tmin[1].max(tmax[1]);
-        let t5 = tmin[2].min(tmax[2]);
-        let t6 = tmin[2].max(tmax[2]);
-
-        let t_enter = t1.max(t3).max(t5);
-        let t_exit = t2.min(t4).min(t6);
-
-        if t_enter < t_exit && t_exit > 0.0 {
-            let point = ray_origin + ray_direction * t_enter;
-            let normal = if (point[0] - self.min[0]).abs() < 1e-3 {
-                Vec3::new(-1.0, 0.0, 0.0)
-            } else if (point[0] - self.max[0]).abs() < 1e-3 {
-                Vec3::new(1.0, 0.0, 0.0)
-            } else if (point[1] - self.min[1]).abs() < 1e-3 {
-                Vec3::new(0.0, -1.0, 0.0)
-            } else if (point[1] - self.max[1]).abs() < 1e-3 {
-                Vec3::new(0.0, 1.0, 0.0)
-            } else if (point[2] - self.min[2]).abs() < 1e-3 {
-                Vec3::new(0.0, 0.0, -1.0)
-            } else {
-                Vec3::new(0.0, 0.0, 1.0)
-            };
-
-            let (u, v) = self.get_uv(&point, &normal);
-            return Intersect::new(point, normal, t_enter, self.material.clone(), u, v);
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let frame = self.frame(ray);
+        let local = frame.as_ref().map_or(*ray, |frame| frame.to_local(ray));
+        if let Some(t) = box_hit_distance(&self.min, &self.max, &local) {
+            let mut intersect = self.surface_hit(&local, t);
+            if let Some(frame) = &frame {
+                frame.to_world(&mut intersect);
+            }
+            let normal = intersect.normal;
+            intersect.set_face_normal(&ray.direction, &normal);
+            return intersect;
         }
 
         Intersect::empty()
     }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        let local = self.frame(ray).map_or(*ray, |frame| frame.to_local(ray));
+        box_hit_distance(&self.min, &self.max, &local).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let rest = Aabb::new(self.min, self.max);
+        match &self.motion {
+            Some(motion) => motion.bounds(&rest, &rest.centroid()),
+            None => rest,
+        }
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let frame = self.frame(ray);
+        let local = frame.as_ref().map_or(*ray, |frame| frame.to_local(ray));
+        match slab_range(&self.min, &self.max, &local) {
+            Some((t_enter, t_exit)) if t_exit > local.t_min => {
+                let mut span = Span {
+                    enter: self.surface_hit(&local, t_enter),
+                    exit: self.surface_hit(&local, t_exit),
+                };
+                if let Some(frame) = &frame {
+                    frame.to_world(&mut span.enter);
+                    frame.to_world(&mut span.exit);
+                }
+                vec![span]
+            }
+            _ => Vec::new(),
+        }
+    }
 }
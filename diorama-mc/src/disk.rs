@@ -0,0 +1,106 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::plane::{orthonormal_basis, ray_plane_distance};
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Disco plano; la textura se proyecta sobre el cuadrado que lo contiene
+pub struct Disk {
+    pub center: Vec3,
+    pub normal: Vec3,
+    pub radius: f32,
+    pub material: Material,
+}
+
+impl Disk {
+    pub fn new(center: Vec3, normal: Vec3, radius: f32, material: Material) -> Self {
+        Disk {
+            center,
+            normal: normal.normalize(),
+            radius,
+            material,
+        }
+    }
+
+    pub fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal);
+        let local = point - self.center;
+        (
+            0.5 + local.dot(&tangent) / (2.0 * self.radius),
+            0.5 + local.dot(&bitangent) / (2.0 * self.radius),
+        )
+    }
+}
+
+impl RayIntersect for Disk {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        if let Some(t) = ray_plane_distance(ray, &self.center, &self.normal) {
+            let point = ray.at(t);
+            if (point - self.center).magnitude_squared() <= self.radius * self.radius {
+                let (u, v) = self.get_uv(&point);
+                let (tangent, bitangent) = orthonormal_basis(&self.normal);
+                // La UV va de 0 a 1 sobre el diámetro
+                let mut intersect = Intersect::new(point, self.normal, t, &self.material, u, v)
+                    .with_tangents(tangent, bitangent)
+                    .with_uv_density(1.0 / (2.0 * self.radius));
+                intersect.set_face_normal(&ray.direction, &self.normal);
+                return intersect;
+            }
+        }
+        Intersect::empty()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        // Extensión del disco en cada eje: r * sqrt(1 - n_i^2)
+        let n = self.normal;
+        let extent = Vec3::new(
+            (1.0 - n.x * n.x).max(0.0).sqrt(),
+            (1.0 - n.y * n.y).max(0.0).sqrt(),
+            (1.0 - n.z * n.z).max(0.0).sqrt(),
+        ) * self.radius;
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn disk() -> Disk {
+        Disk::new(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black()),
+        )
+    }
+
+    #[test]
+    fn uv_spans_the_diameter() {
+        let disk = disk();
+        let down = Vec3::new(0.0, -1.0, 0.0);
+        let center = disk.ray_intersect(&Ray::new(Vec3::new(0.0, 5.0, 0.0), down));
+        assert!(center.is_intersecting && center.front_face);
+        assert!((center.distance - 4.0).abs() < 1e-4);
+        assert!((center.u - 0.5).abs() < 1e-4 && (center.v - 0.5).abs() < 1e-4);
+
+        // En el borde, una coordenada llega a 0 o a 1
+        let (tangent, _) = orthonormal_basis(&disk.normal);
+        let edge = disk.ray_intersect(&Ray::new(Vec3::new(0.0, 5.0, 0.0) + tangent * 1.99, down));
+        assert!(edge.is_intersecting);
+        assert!((edge.u - 1.0).abs() < 0.01 && (edge.v - 0.5).abs() < 1e-4);
+        assert!((edge.uv_density - 0.25).abs() < 1e-6);
+    }
+
+    #[test]
+    fn misses_outside_the_radius() {
+        let disk = disk();
+        // Dentro del cuadrado que contiene al disco, pero fuera del círculo
+        let ray = Ray::new(Vec3::new(1.6, 5.0, 1.6), Vec3::new(0.0, -1.0, 0.0));
+        assert!(!disk.ray_intersect(&ray).is_intersecting);
+        let bounds = disk.bounding_box();
+        assert!((bounds.max - Vec3::new(2.0, 1.0, 2.0)).magnitude() < 1e-4);
+    }
+}
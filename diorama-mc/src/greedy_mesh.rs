@@ -0,0 +1,218 @@
+use crate::aabb::Aabb;
+use crate::cube::{box_face_hit, Cube};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::scene::SceneObject;
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+
+// Rectángulo alineado a los ejes que cubre las caras visibles de varias celdas vecinas con el mismo material.
+// La textura se repite por celda: cada bloque muestra la misma UV que tendría como Cube
+pub struct BlockFace {
+    pub min: Vec3, // En el eje de la normal min y max coinciden
+    pub max: Vec3,
+    pub axis: usize,
+    pub normal: Vec3, // Normal exterior (±1 sobre `axis`)
+    pub material: Material,
+}
+
+impl BlockFace {
+    pub fn new(min: Vec3, max: Vec3, axis: usize, normal: Vec3, material: Material) -> Self {
+        BlockFace {
+            min,
+            max,
+            axis,
+            normal,
+            material,
+        }
+    }
+
+    fn hit(&self, ray: &Ray) -> Option<f32> {
+        let t = (self.min[self.axis] - ray.origin[self.axis]) / ray.direction[self.axis];
+        if !t.is_finite() || !ray.in_range(t) {
+            return None;
+        }
+        let point = ray.at(t);
+        let inside = (0..3)
+            .filter(|&i| i != self.axis)
+            .all(|i| point[i] >= self.min[i] && point[i] <= self.max[i]);
+        if inside {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    // Celda unitaria (detrás de la cara) a la que pertenece el punto
+    fn cell(&self, point: &Vec3) -> (Vec3, Vec3) {
+        let cell_min = (point - self.normal * 0.5).map(|c| c.floor());
+        (cell_min, cell_min + Vec3::new(1.0, 1.0, 1.0))
+    }
+}
+
+impl RayIntersect for BlockFace {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.hit(ray) {
+            Some(t) => {
+                let point = ray.at(t);
+                let (cell_min, cell_max) = self.cell(&point);
+                let mut intersect = box_face_hit(&cell_min, &cell_max, point, self.normal, t, &self.material);
+                intersect.set_face_normal(&ray.direction, &self.normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.hit(ray).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::new(self.min, self.max)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeStats {
+    pub blocks: usize,        // Cubos recibidos
+    pub hidden_blocks: usize, // Bloques sin ninguna cara visible, descartados
+    pub visible_faces: usize, // Caras unitarias visibles antes de fusionar
+    pub primitives: usize,    // Primitivas resultantes (caras fusionadas más cubos sin tocar)
+}
+
+impl MergeStats {
+    pub fn saved(&self) -> usize {
+        self.blocks.saturating_sub(self.primitives)
+    }
+}
+
+// Material de cada cara de una capa, indexado por las dos coordenadas dentro del plano
+type Layer = HashMap<(i32, i32), usize>;
+
+// Solo se procesan bloques unitarios, alineados a la rejilla y quietos
+fn grid_cell(cube: &Cube) -> Option<[i32; 3]> {
+    let size = cube.max - cube.min;
+    let aligned = (0..3).all(|i| (size[i] - 1.0).abs() < 1e-4 && (cube.min[i] - cube.min[i].round()).abs() < 1e-4);
+    if aligned && cube.motion.is_none() {
+        Some([cube.min.x.round() as i32, cube.min.y.round() as i32, cube.min.z.round() as i32])
+    } else {
+        None
+    }
+}
+
+// Descarta los bloques totalmente enterrados, quita las caras que tocan otro bloque opaco y fusiona
+// las caras coplanares vecinas con el mismo material en rectángulos (greedy meshing).
+// Los bloques transparentes o con alfa y los que no encajan en la rejilla se devuelven como Cube sin cambios
+pub fn merge_blocks(cubes: Vec<Cube>) -> (Vec<SceneObject>, MergeStats) {
+    let mut stats = MergeStats {
+        blocks: cubes.len(),
+        ..MergeStats::default()
+    };
+    let mut objects: Vec<SceneObject> = Vec::new();
+
+    // Bloques opacos por celda, con el índice de su material en `materials`
+    let mut materials: Vec<Material> = Vec::new();
+    let mut solid: HashMap<[i32; 3], usize> = HashMap::new();
+    for cube in cubes {
+        match grid_cell(&cube) {
+            Some(cell) if cube.material.is_opaque() => {
+                let id = match materials.iter().position(|m| m.id == cube.material.id) {
+                    Some(id) => id,
+                    None => {
+                        materials.push(cube.material.clone());
+                        materials.len() - 1
+                    }
+                };
+                solid.insert(cell, id);
+            }
+            _ => objects.push(Box::new(cube)),
+        }
+    }
+
+    // Caras visibles agrupadas por dirección y capa; la clave interna son las otras dos coordenadas
+    let mut layers: HashMap<(usize, i32, i32), Layer> = HashMap::new();
+    for (cell, &id) in &solid {
+        let mut visible = false;
+        for axis in 0..3 {
+            for sign in [-1, 1] {
+                let mut neighbour = *cell;
+                neighbour[axis] += sign;
+                if solid.contains_key(&neighbour) {
+                    continue;
+                }
+                visible = true;
+                stats.visible_faces += 1;
+                let plane = if sign > 0 { cell[axis] + 1 } else { cell[axis] };
+                let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+                layers
+                    .entry((axis, sign, plane))
+                    .or_default()
+                    .insert((cell[a], cell[b]), id);
+            }
+        }
+        if !visible {
+            stats.hidden_blocks += 1;
+        }
+    }
+
+    for ((axis, sign, plane), faces) in layers {
+        let (a, b) = ((axis + 1) % 3, (axis + 2) % 3);
+        let a_min = faces.keys().map(|k| k.0).min().unwrap();
+        let a_max = faces.keys().map(|k| k.0).max().unwrap();
+        let b_min = faces.keys().map(|k| k.1).min().unwrap();
+        let b_max = faces.keys().map(|k| k.1).max().unwrap();
+        let width = (a_max - a_min + 1) as usize;
+        let height = (b_max - b_min + 1) as usize;
+
+        let mut mask: Vec<Option<usize>> = vec![None; width * height];
+        for (&(i, j), &id) in &faces {
+            mask[(j - b_min) as usize * width + (i - a_min) as usize] = Some(id);
+        }
+
+        for j in 0..height {
+            let mut i = 0;
+            while i < width {
+                let id = match mask[j * width + i] {
+                    Some(id) => id,
+                    None => {
+                        i += 1;
+                        continue;
+                    }
+                };
+
+                // Extender a lo ancho y luego fila por fila mientras todo el tramo coincida
+                let mut w = 1;
+                while i + w < width && mask[j * width + i + w] == Some(id) {
+                    w += 1;
+                }
+                let mut h = 1;
+                while j + h < height && (i..i + w).all(|x| mask[(j + h) * width + x] == Some(id)) {
+                    h += 1;
+                }
+                for y in j..j + h {
+                    for x in i..i + w {
+                        mask[y * width + x] = None;
+                    }
+                }
+
+                let mut min = Vec3::zeros();
+                min[axis] = plane as f32;
+                min[a] = (a_min + i as i32) as f32;
+                min[b] = (b_min + j as i32) as f32;
+                let mut max = min;
+                max[a] += w as f32;
+                max[b] += h as f32;
+                let mut normal = Vec3::zeros();
+                normal[axis] = sign as f32;
+                objects.push(Box::new(BlockFace::new(min, max, axis, normal, materials[id].clone())));
+
+                i += w;
+            }
+        }
+    }
+
+    stats.primitives = objects.len();
+    (objects, stats)
+}
@@ -0,0 +1,388 @@
+extern crate image;
+use crate::aabb::Aabb;
+use crate::cube::{box_hit_distance, box_normal, box_uv, slab_range, Face};
+use crate::material::Material;
+use crate::mesh::triangle_hit;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use image::ImageReader;
+use nalgebra_glm::Vec3;
+
+// Margen de los nodos para que las zonas planas no queden con grosor cero y los rayos
+// que corren justo sobre el borde entre celdas no se pierdan
+const NODE_PADDING: f32 = 1e-4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HeightfieldMode {
+    Blocky, // Cada píxel es una columna de bloques con la altura redondeada al bloque
+    Smooth, // Cada píxel es un vértice y las celdas se cubren con dos triángulos
+}
+
+// Un nivel de la pirámide de alturas: mínimo y máximo de cada bloque de 2^nivel celdas por lado
+struct MipLevel {
+    width: usize,
+    depth: usize,
+    min: Vec<f32>,
+    max: Vec<f32>,
+}
+
+struct TerrainHit {
+    t: f32,
+    normal: Vec3,    // Normal exterior usada para sombrear
+    geometric: Vec3, // Normal de la superficie real, decide qué lado golpeó el rayo
+    u: f32,
+    v: f32,
+    face: Face,
+}
+
+// Terreno a partir de una imagen en escala de grises, recorrido con una pirámide min/max
+pub struct Heightfield {
+    pub origin: Vec3,    // Esquina mínima del terreno; la altura 0 de la imagen queda en origin.y
+    pub cell_size: f32,  // Tamaño horizontal de cada celda
+    pub width: usize,    // Muestras en X
+    pub depth: usize,    // Muestras en Z
+    heights: Vec<f32>,   // Altura absoluta de cada muestra
+    mips: Vec<MipLevel>, // mips[0] son las celdas; el último nivel tiene un único nodo
+    pub mode: HeightfieldMode,
+    pub material: Material,
+}
+
+impl Heightfield {
+    pub fn new(
+        heights: Vec<f32>,
+        width: usize,
+        depth: usize,
+        origin: Vec3,
+        cell_size: f32,
+        mode: HeightfieldMode,
+        material: Material,
+    ) -> Self {
+        assert_eq!(heights.len(), width * depth, "Heightfield size does not match its samples");
+        let min_samples = if mode == HeightfieldMode::Smooth { 2 } else { 1 };
+        assert!(
+            width >= min_samples && depth >= min_samples,
+            "Heightfield is too small for the selected mode"
+        );
+
+        // En modo por bloques la altura se ajusta a múltiplos del tamaño de bloque
+        let heights = match mode {
+            HeightfieldMode::Blocky => heights
+                .iter()
+                .map(|h| origin.y + ((h - origin.y) / cell_size).round() * cell_size)
+                .collect(),
+            HeightfieldMode::Smooth => heights,
+        };
+
+        let mut heightfield = Heightfield {
+            origin,
+            cell_size,
+            width,
+            depth,
+            heights,
+            mips: Vec::new(),
+            mode,
+            material,
+        };
+        heightfield.build_mips();
+        heightfield
+    }
+
+    // Cada píxel aporta `height_scale * gris / 255` de altura sobre origin.y
+    pub fn from_image(
+        file_path: &str,
+        origin: Vec3,
+        cell_size: f32,
+        height_scale: f32,
+        mode: HeightfieldMode,
+        material: Material,
+    ) -> Self {
+        let img = ImageReader::open(file_path)
+            .expect("Failed to open file")
+            .decode()
+            .expect("Failed to decode image")
+            .to_luma8();
+
+        let width = img.width() as usize;
+        let depth = img.height() as usize;
+        let heights = img
+            .pixels()
+            .map(|pixel| origin.y + pixel.0[0] as f32 / 255.0 * height_scale)
+            .collect();
+
+        Heightfield::new(heights, width, depth, origin, cell_size, mode, material)
+    }
+
+    pub fn height(&self, x: usize, z: usize) -> f32 {
+        self.heights[z * self.width + x]
+    }
+
+    // Número de celdas por lado: en modo suave las celdas quedan entre muestras
+    fn cells(&self) -> (usize, usize) {
+        match self.mode {
+            HeightfieldMode::Blocky => (self.width, self.depth),
+            HeightfieldMode::Smooth => (self.width - 1, self.depth - 1),
+        }
+    }
+
+    fn build_mips(&mut self) {
+        let (width, depth) = self.cells();
+        let mut min = Vec::with_capacity(width * depth);
+        let mut max = Vec::with_capacity(width * depth);
+        for z in 0..depth {
+            for x in 0..width {
+                let (lo, hi) = match self.mode {
+                    HeightfieldMode::Blocky => (self.height(x, z), self.height(x, z)),
+                    HeightfieldMode::Smooth => {
+                        let corners = [
+                            self.height(x, z),
+                            self.height(x + 1, z),
+                            self.height(x, z + 1),
+                            self.height(x + 1, z + 1),
+                        ];
+                        (
+                            corners.iter().cloned().fold(f32::INFINITY, f32::min),
+                            corners.iter().cloned().fold(f32::NEG_INFINITY, f32::max),
+                        )
+                    }
+                };
+                min.push(lo);
+                max.push(hi);
+            }
+        }
+        self.mips = vec![MipLevel { width, depth, min, max }];
+
+        // Cada nivel agrupa 2x2 nodos del anterior hasta quedar uno solo
+        while self.mips.last().is_some_and(|level| level.width > 1 || level.depth > 1) {
+            let prev = self.mips.last().unwrap();
+            let width = prev.width.div_ceil(2);
+            let depth = prev.depth.div_ceil(2);
+            let mut min = vec![f32::INFINITY; width * depth];
+            let mut max = vec![f32::NEG_INFINITY; width * depth];
+            for z in 0..prev.depth {
+                for x in 0..prev.width {
+                    let parent = (z / 2) * width + x / 2;
+                    min[parent] = min[parent].min(prev.min[z * prev.width + x]);
+                    max[parent] = max[parent].max(prev.max[z * prev.width + x]);
+                }
+            }
+            self.mips.push(MipLevel { width, depth, min, max });
+        }
+    }
+
+    // Caja de un nodo de la pirámide; en modo por bloques las columnas son sólidas hasta origin.y
+    fn node_bounds(&self, level: usize, x: usize, z: usize) -> Aabb {
+        let mip = &self.mips[level];
+        let index = z * mip.width + x;
+        let (cells_x, cells_z) = self.cells();
+        let span = 1 << level;
+        let x0 = x * span;
+        let z0 = z * span;
+        let x1 = ((x + 1) * span).min(cells_x);
+        let z1 = ((z + 1) * span).min(cells_z);
+        let bottom = match self.mode {
+            HeightfieldMode::Blocky => self.origin.y,
+            HeightfieldMode::Smooth => mip.min[index],
+        };
+        let padding = Vec3::new(NODE_PADDING, NODE_PADDING, NODE_PADDING);
+        Aabb::new(
+            Vec3::new(
+                self.origin.x + x0 as f32 * self.cell_size,
+                bottom,
+                self.origin.z + z0 as f32 * self.cell_size,
+            ) - padding,
+            Vec3::new(
+                self.origin.x + x1 as f32 * self.cell_size,
+                mip.max[index],
+                self.origin.z + z1 as f32 * self.cell_size,
+            ) + padding,
+        )
+    }
+
+    // Desciende por la pirámide visitando los hijos en el orden en que el rayo los cruza;
+    // como los hijos no se solapan en XZ, el primer impacto encontrado es el más cercano
+    fn visit(&self, level: usize, x: usize, z: usize, ray: &Ray) -> Option<TerrainHit> {
+        if level == 0 {
+            return match self.mode {
+                HeightfieldMode::Blocky => self.hit_column(x, z, ray),
+                HeightfieldMode::Smooth => self.hit_cell(x, z, ray),
+            };
+        }
+
+        let child_level = &self.mips[level - 1];
+        let mut children: Vec<(f32, usize, usize)> = Vec::with_capacity(4);
+        for (cx, cz) in [(2 * x, 2 * z), (2 * x + 1, 2 * z), (2 * x, 2 * z + 1), (2 * x + 1, 2 * z + 1)] {
+            if cx >= child_level.width || cz >= child_level.depth {
+                continue;
+            }
+            let bounds = self.node_bounds(level - 1, cx, cz);
+            if let Some((t_enter, t_exit)) = slab_range(&bounds.min, &bounds.max, ray) {
+                if t_exit > ray.t_min && t_enter < ray.t_max {
+                    children.push((t_enter, cx, cz));
+                }
+            }
+        }
+        children.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(std::cmp::Ordering::Equal));
+
+        children
+            .into_iter()
+            .find_map(|(_, cx, cz)| self.visit(level - 1, cx, cz, ray))
+    }
+
+    fn hit_column(&self, x: usize, z: usize, ray: &Ray) -> Option<TerrainHit> {
+        let min = Vec3::new(
+            self.origin.x + x as f32 * self.cell_size,
+            self.origin.y,
+            self.origin.z + z as f32 * self.cell_size,
+        );
+        let max = Vec3::new(min.x + self.cell_size, self.height(x, z), min.z + self.cell_size);
+        let t = box_hit_distance(&min, &max, ray)?;
+        let point = ray.at(t);
+        let normal = box_normal(&min, &max, &point);
+
+        // La columna se texturiza bloque a bloque, como si fueran cubos apilados
+        let inside = point - normal * (self.cell_size * 0.5);
+        let block_y = self.origin.y + ((inside.y - self.origin.y) / self.cell_size).floor() * self.cell_size;
+        let block_min = Vec3::new(min.x, block_y, min.z);
+        let block_max = block_min + Vec3::new(self.cell_size, self.cell_size, self.cell_size);
+        let (u, v) = box_uv(self.material.uv_mapping(), &block_min, &block_max, &point, &normal);
+        let face = Face::from_normal(&normal);
+
+        Some(TerrainHit { t, normal, geometric: normal, u, v, face })
+    }
+
+    fn hit_cell(&self, x: usize, z: usize, ray: &Ray) -> Option<TerrainHit> {
+        let corner = |cx: usize, cz: usize| {
+            Vec3::new(
+                self.origin.x + cx as f32 * self.cell_size,
+                self.height(cx, cz),
+                self.origin.z + cz as f32 * self.cell_size,
+            )
+        };
+        let p00 = corner(x, z);
+        let p10 = corner(x + 1, z);
+        let p01 = corner(x, z + 1);
+        let p11 = corner(x + 1, z + 1);
+        let n00 = self.sample_normal(x, z);
+        let n10 = self.sample_normal(x + 1, z);
+        let n01 = self.sample_normal(x, z + 1);
+        let n11 = self.sample_normal(x + 1, z + 1);
+
+        // Dos triángulos por celda, ordenados para que su normal apunte hacia +Y
+        let triangles = [((p00, n00), (p01, n01), (p11, n11)), ((p00, n00), (p11, n11), (p10, n10))];
+        let mut closest: Option<TerrainHit> = None;
+        for ((a, na), (b, nb), (c, nc)) in triangles {
+            if let Some((t, w1, w2)) = triangle_hit(&a, &b, &c, ray) {
+                if closest.as_ref().is_some_and(|hit| hit.t <= t) {
+                    continue;
+                }
+                let w0 = 1.0 - w1 - w2;
+                let normal = (na * w0 + nb * w1 + nc * w2).normalize();
+                let geometric = (b - a).cross(&(c - a)).normalize();
+
+                // Cada celda muestra la cara superior completa de la textura del bloque
+                let point = ray.at(t);
+                let cell_max = Vec3::new(p11.x, point.y + self.cell_size, p11.z);
+                let up = Vec3::new(0.0, 1.0, 0.0);
+                let (u, v) = box_uv(self.material.uv_mapping(), &p00, &cell_max, &point, &up);
+
+                closest = Some(TerrainHit { t, normal, geometric, u, v, face: Face::Up });
+            }
+        }
+        closest
+    }
+
+    // Normal de una muestra por diferencias centrales de la altura
+    fn sample_normal(&self, x: usize, z: usize) -> Vec3 {
+        let left = self.height(x.saturating_sub(1), z);
+        let right = self.height((x + 1).min(self.width - 1), z);
+        let back = self.height(x, z.saturating_sub(1));
+        let front = self.height(x, (z + 1).min(self.depth - 1));
+        let dx = ((x + 1).min(self.width - 1) - x.saturating_sub(1)) as f32 * self.cell_size;
+        let dz = ((z + 1).min(self.depth - 1) - z.saturating_sub(1)) as f32 * self.cell_size;
+        Vec3::new(-(right - left) / dx, 1.0, -(front - back) / dz).normalize()
+    }
+
+    fn closest_hit(&self, ray: &Ray) -> Option<TerrainHit> {
+        let root = self.mips.len() - 1;
+        let bounds = self.node_bounds(root, 0, 0);
+        let (t_enter, t_exit) = slab_range(&bounds.min, &bounds.max, ray)?;
+        if t_exit <= ray.t_min || t_enter >= ray.t_max {
+            return None;
+        }
+        self.visit(root, 0, 0, ray)
+    }
+}
+
+impl RayIntersect for Heightfield {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.closest_hit(ray) {
+            Some(hit) => {
+                let point = ray.at(hit.t);
+                let mut intersect =
+                    Intersect::new(point, hit.normal, hit.t, &self.material, hit.u, hit.v).with_face(hit.face);
+                // Igual que en las mallas: el lado lo decide la normal geométrica
+                if ray.direction.dot(&hit.geometric) > 0.0 {
+                    intersect.front_face = false;
+                    intersect.normal = -hit.normal;
+                }
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.closest_hit(ray).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.node_bounds(self.mips.len() - 1, 0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn terrain(heights: Vec<f32>, width: usize, depth: usize, mode: HeightfieldMode) -> Heightfield {
+        let material = Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black());
+        Heightfield::new(heights, width, depth, Vec3::zeros(), 1.0, mode, material)
+    }
+
+    #[test]
+    fn blocky_traversal_finds_the_tall_column() {
+        // Terreno plano de 8x8 con una sola columna de 3 bloques en (5, 2)
+        let mut heights = vec![0.0; 64];
+        heights[2 * 8 + 5] = 3.0;
+        let terrain = terrain(heights, 8, 8, HeightfieldMode::Blocky);
+        assert_eq!(terrain.bounding_box().max.y, 3.0 + NODE_PADDING);
+
+        let top = terrain.ray_intersect(&Ray::new(Vec3::new(5.5, 10.0, 2.5), Vec3::new(0.0, -1.0, 0.0)));
+        assert!(top.is_intersecting && top.front_face);
+        assert!((top.distance - 7.0).abs() < 1e-4);
+        assert_eq!(top.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        // De lado, a media altura, se pasa sobre las celdas bajas hasta la cara oeste de la columna
+        let side = terrain.ray_intersect(&Ray::new(Vec3::new(-5.0, 1.5, 2.5), Vec3::new(1.0, 0.0, 0.0)));
+        assert!(side.is_intersecting);
+        assert!((side.distance - 10.0).abs() < 1e-4);
+        assert_eq!(side.normal, Vec3::new(-1.0, 0.0, 0.0));
+
+        // En otra fila, o por encima de la columna, no hay nada que tocar
+        let beside = Ray::new(Vec3::new(-5.0, 1.5, 4.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(!terrain.ray_intersect(&beside).is_intersecting);
+        assert!(!terrain.occluded(&Ray::new(Vec3::new(-5.0, 3.5, 2.5), Vec3::new(1.0, 0.0, 0.0))));
+    }
+
+    #[test]
+    fn smooth_surface_interpolates_between_samples() {
+        // Rampa: la altura sube con x de 0 a 1
+        let terrain = terrain(vec![0.0, 1.0, 0.0, 1.0], 2, 2, HeightfieldMode::Smooth);
+        let hit = terrain.ray_intersect(&Ray::new(Vec3::new(0.25, 5.0, 0.5), Vec3::new(0.0, -1.0, 0.0)));
+        assert!(hit.is_intersecting && hit.front_face);
+        assert!((hit.distance - 4.75).abs() < 1e-4);
+        let ramp = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        assert!((hit.normal - ramp).magnitude() < 1e-4);
+    }
+}
@@ -1,10 +1,9 @@
 use core::f32;
 use minifb::{Key, Window, WindowOptions};
 use nalgebra_glm::Vec3;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use std::f32::consts::PI;
-use std::f32::INFINITY;
 
 mod framebuffer;
 use framebuffer::Framebuffer;
@@ -12,6 +11,9 @@ use framebuffer::Framebuffer;
 mod sphere;
 use sphere::Sphere;
 
+mod ray;
+use ray::Ray;
+
 mod ray_intersect;
 use ray_intersect::{Intersect, RayIntersect};
 
@@ -22,82 +24,211 @@ mod camera;
 use camera::Camera;
 
 mod material;
-use material::Material;
+use material::{Faces, Material};
 
 mod light;
 use light::Light;
 
 mod texture;
 use std::sync::Arc;
-use texture::Texture;
+use texture::{Filter, Texture};
 
 mod cube;
 use cube::Cube;
 
+mod aabb;
+use aabb::Aabb;
+
+mod bvh;
+
+mod voxel_grid;
+use voxel_grid::VoxelGrid;
+
+mod scene;
+use scene::{Scene, SceneObject};
+
+mod mesh;
+
+mod obj;
+use obj::load_obj;
+
+mod plane;
+use plane::Plane;
+
+mod quad;
+use quad::Quad;
+
+mod disk;
+use disk::Disk;
+
+mod transform;
+
+mod csg;
+use csg::{Csg, CsgOperation};
+
+mod partial_block;
+use partial_block::{BlockShape, Connections, Facing, Half, PartialBlock};
+
+mod sdf;
+use sdf::{Sdf, SdfObject};
+
+mod heightfield;
+use heightfield::{Heightfield, HeightfieldMode};
+mod motion;
+use motion::{Motion, Pose};
+mod greedy_mesh;
+mod pbr;
+use pbr::{base_reflectance, color_to_vec, fresnel_schlick, ggx_brdf, vec_to_color};
+use greedy_mesh::merge_blocks;
+mod atlas;
+mod animation;
+mod procedural;
+mod biome;
+use biome::{Biome, BiomeColors, TINT_GRASS};
+use procedural::Procedural;
+
 use rayon::prelude::*;
+use rand::Rng;
 
 const BIAS: f32 = 0.001;
+// Superficies con alfa o transparentes que puede cruzar de largo un rayo (de cámara o de sombra)
+const MAX_ALPHA_LAYERS: u32 = 16;
 const SKYBOX_COLOR: Color = Color::new(69, 142, 228);
 
 const AMBIENT_LIGHT_COLOR: Color = Color::new(50, 50, 50);
 const AMBIENT_INTENSITY: f32 = 0.3; // Intensidad de la luz ambiental
+const SHADOW_INTENSITY: f32 = 0.5; // Cuánta luz bloquea un objeto entre el punto y la luz
 
 fn offset_point(intersect: &Intersect, direction: &Vec3) -> Vec3 {
+    // Desplazar hacia el lado de la superficie al que va el rayo (adentro si se refracta)
     let offset = intersect.normal * BIAS;
-    intersect.point + offset
+    if direction.dot(&intersect.normal) < 0.0 {
+        intersect.point - offset
+    } else {
+        intersect.point + offset
+    }
 }
 
 fn reflect(incident: &Vec3, normal: &Vec3) -> Vec3 {
     incident - 2.0 * incident.dot(normal) * normal
 }
 
-fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32) -> Vec3 {
+// La normal apunta contra el rayo; `front_face` indica si el rayo entra o sale del material
+fn refract(incident: &Vec3, normal: &Vec3, eta_t: f32, front_face: bool) -> Vec3 {
+    #[allow(clippy::manual_clamp)]
     let cosi = -incident.dot(normal).max(-1.0).min(1.0);
 
-    let n_cosi: f32;
-    let eta: f32;
-    let n_normal: Vec3;
-
-    if cosi < 0.0 {
+    let eta = if front_face {
         // Entering
-        n_cosi = -cosi;
-        eta = 1.0 / eta_t;
-        n_normal = -normal;
+        1.0 / eta_t
     } else {
         // Leaving
-        n_cosi = cosi;
-        eta = eta_t;
-        n_normal = *normal;
-    }
+        eta_t
+    };
 
-    let k = 1.0 - eta * eta * (1.0 - n_cosi * n_cosi);
+    let k = 1.0 - eta * eta * (1.0 - cosi * cosi);
 
-    if k > 0.0 {
+    if k < 0.0 {
         // Total internal reflection
-        reflect(incident, &n_normal)
+        reflect(incident, normal)
     } else {
-        incident * eta + (eta * n_cosi - k.sqrt()) * n_normal
+        incident * eta + (eta * cosi - k.sqrt()) * normal
     }
 }
 
-fn cast_shadow(intersect: &Intersect, light: &Light, objects: &[Cube]) -> f32 {
+// Sombra por canal (0 = nada tapa la luz). `time` es el instante dentro del obturador; `clock`, el
+// reloj de la escena para las texturas animadas
+fn cast_shadow(intersect: &Intersect, light: &Light, objects: &Scene, time: f32, clock: f32) -> Vec3 {
     let light_dir = (light.position - intersect.point).normalize();
     let light_distance = (light.position - intersect.point).magnitude();
 
+    // Solo importa si hay algo entre el punto y la luz, no cuál es el objeto más cercano
     let shadow_ray_origin = offset_point(intersect, &light_dir);
-    let mut shadow_intensity = 0.0;
+    let shadow_ray = Ray::with_range(shadow_ray_origin, light_dir, 0.0, light_distance).with_time(time);
 
-    for object in objects {
-        let shadow_intersect = object.ray_intersect(&shadow_ray_origin, &light_dir);
-        if shadow_intersect.is_intersecting && shadow_intersect.distance < light_distance {
-            let distance_ratio = shadow_intersect.distance / light_distance;
-            shadow_intensity = 0.50 - distance_ratio.powf(2.0).min(1.0);
+    // Un bloqueador opaco da la sombra completa sin buscar el impacto más cercano
+    match objects.occluder(&shadow_ray) {
+        None => return Vec3::zeros(),
+        Some(material) if material.is_opaque() => return Vec3::repeat(SHADOW_INTENSITY),
+        Some(_) => {}
+    }
 
+    // Lo que tapa la luz puede dejarla pasar: se recorren los impactos hasta la luz para que los texeles transparentes
+    // (hojas, vidrio con alfa) dejen pasar su parte y los materiales transparentes (vidrio, agua,
+    // hielo) la dejen pasar teñida por lo que absorben adentro
+    let mut transmittance = Vec3::new(1.0, 1.0, 1.0);
+    let mut current = shadow_ray;
+    // Material y distancia por donde el rayo entró al último sólido transparente
+    let mut entry: Option<(&Material, f32)> = None;
+    loop {
+        let hit = objects.ray_intersect(&current);
+        if !hit.is_intersecting {
             break;
         }
+        let coverage = hit.material.coverage(hit.face, hit.u, hit.v, 0.0, clock);
+        // Lo que deja pasar la superficie: nada si es opaca; si es transparente, su transparencia
+        // al entrar y, al salir del mismo material, lo que no absorbió en el espesor atravesado.
+        // Una cara trasera sin entrada es una superficie de una sola cara (un vidrio plano, un
+        // quad): deja pasar una sola capa
+        let passed = if hit.material.albedo[3] <= 0.0 {
+            Vec3::zeros()
+        } else if hit.front_face {
+            entry = Some((hit.material, hit.distance));
+            Vec3::repeat(hit.material.albedo[3])
+        } else {
+            match entry.take() {
+                Some((material, distance)) if std::ptr::eq(material, hit.material) => {
+                    material.transmittance(hit.distance - distance)
+                }
+                _ => Vec3::repeat(hit.material.albedo[3]),
+            }
+        };
+        transmittance = transmittance.component_mul(&(Vec3::repeat(1.0 - coverage) + passed * coverage));
+        if transmittance.max() <= 0.0 {
+            break;
+        }
+        current = match past_hit(&current, &hit) {
+            Some(next) => next,
+            None => break,
+        };
+    }
+    (Vec3::new(1.0, 1.0, 1.0) - transmittance) * SHADOW_INTENSITY
+}
+
+// El mismo rayo, desde justo después del impacto (para atravesar texeles transparentes).
+// None si ya atravesó MAX_ALPHA_LAYERS capas: una fila larga de hojas no puede agotar la pila
+fn past_hit(ray: &Ray, intersect: &Intersect) -> Option<Ray> {
+    if ray.layers >= MAX_ALPHA_LAYERS {
+        return None;
+    }
+    let mut ray = *ray;
+    ray.t_min = intersect.distance + BIAS / ray.direction.magnitude();
+    ray.layers += 1;
+    Some(ray)
+}
+
+// Lleva la normal del mapa (espacio tangente) al mundo con el marco tangente del impacto
+fn apply_normal_map(intersect: &mut Intersect, clock: f32) {
+    let mapped = match intersect.material.get_normal(intersect.face, intersect.u, intersect.v, clock) {
+        Some(normal) => normal,
+        None => return,
+    };
+
+    // Gram-Schmidt: el marco puede no ser ortogonal a la normal (p. ej. tras una transformación)
+    let normal = intersect.normal;
+    let tangent = intersect.tangent - normal * normal.dot(&intersect.tangent);
+    if tangent.magnitude_squared() < 1e-8 {
+        return;
     }
+    let tangent = tangent.normalize();
+    let bitangent = intersect.bitangent - normal * normal.dot(&intersect.bitangent) - tangent * tangent.dot(&intersect.bitangent);
+    let bitangent = if bitangent.magnitude_squared() < 1e-8 {
+        normal.cross(&tangent)
+    } else {
+        bitangent.normalize()
+    };
 
-    shadow_intensity
+    intersect.normal = (tangent * mapped.x + bitangent * mapped.y + normal * mapped.z).normalize();
 }
 
 fn get_skybox_color(ray_direction: &Vec3, skybox: &Texture) -> Color {
@@ -112,6 +243,7 @@ fn get_skybox_color(ray_direction: &Vec3, skybox: &Texture) -> Color {
     skybox.get_color_at_uv(u, v)
 }
 
+#[allow(clippy::manual_clamp, clippy::unnecessary_min_or_max)]
 fn clamp_color(color: Color) -> Color {
     Color::new(
         color.r().min(255).max(0),
@@ -121,122 +253,183 @@ fn clamp_color(color: Color) -> Color {
 }
 
 pub fn cast_ray(
-    ray_origin: &Vec3,
-    ray_direction: &Vec3,
-    objects: &[Cube],
+    ray: &Ray,
+    objects: &Scene,
     lights: &[Light], // Cambiamos de light a lights
     skybox: &Texture, 
+    clock: f32, // Segundos desde el inicio, para las texturas animadas
     depth: u32,
+    medium: Option<&Material>, // Material en el que entró el rayo al refractarse en su origen
 ) -> Color {
     if depth >= 3 {
         return SKYBOX_COLOR;
     }
 
-    let mut intersect = Intersect::empty();
-    let mut zbuffer = INFINITY;
-
     // Encontrar la intersección más cercana
-    for object in objects {
-        let i = object.ray_intersect(ray_origin, ray_direction);
+    let mut intersect = objects.ray_intersect(ray);
 
-        if i.is_intersecting && i.distance < zbuffer {
-            zbuffer = i.distance;
-            intersect = i;
-        }
+    if !intersect.is_intersecting {
+        return get_skybox_color(&ray.direction, skybox);
     }
 
-    if !intersect.is_intersecting {
-        return get_skybox_color(ray_direction, skybox);
+    // Ancho del píxel sobre la superficie (crece en ángulos rasantes), en unidades de UV, para el mipmap
+    let cos_view = intersect.normal.dot(&ray.direction.normalize()).abs().max(0.2);
+    let footprint = ray.footprint(intersect.distance) / cos_view * intersect.uv_density;
+    let material = intersect.material;
+    let (texel, coverage) = material.get_face_texel(intersect.face, intersect.u, intersect.v, footprint, clock);
+
+    // Por los texeles transparentes el mismo rayo sigue de largo, sin gastar profundidad
+    // (pero con su propio tope de capas)
+    if coverage <= 0.0 {
+        return match past_hit(ray, &intersect) {
+            Some(next) => cast_ray(&next, objects, lights, skybox, clock, depth, medium),
+            None => SKYBOX_COLOR,
+        };
     }
 
+    // El detalle del mapa de normales se aplica antes de cualquier cálculo de luz
+    apply_normal_map(&mut intersect, clock);
+
     // Luz ambiental
     let ambient_light = AMBIENT_LIGHT_COLOR * AMBIENT_INTENSITY;
     let mut total_light = ambient_light;
 
-    // Procesar la contribución de cada fuente de luz
+    // Parámetros PBR del material en el punto (los de Phong se convierten) y color base
+    let pbr = material.pbr_at(intersect.face, intersect.u, intersect.v, footprint, clock);
+    let base_color = color_to_vec(texel);
+    let view_dir = (ray.origin - intersect.point).normalize();
+
+    // Procesar la contribución de cada fuente de luz con el BRDF de GGX
     for light in lights {
         let light_dir = (light.position - intersect.point).normalize();
-        let view_dir = (ray_origin - intersect.point).normalize();
-        let reflect_dir = reflect(&-light_dir, &intersect.normal).normalize();
+        let n_dot_l = intersect.normal.dot(&light_dir);
+        if n_dot_l <= 0.0 {
+            continue;
+        }
 
         // Calcular la intensidad de la sombra
-        let shadow_intensity = cast_shadow(&intersect, light, objects);
-        let light_intensity = light.intensity * (1.0 - shadow_intensity);
-
-        // Calcular componentes difusos y especulares
-        let diffuse_intensity = intersect.normal.dot(&light_dir).max(0.0).min(1.0);
-        let diffuse_color = intersect.material.get_diffuse_color(intersect.u, intersect.v);
-        let diffuse = diffuse_color * intersect.material.albedo[0] * diffuse_intensity * light_intensity;
-
-        let specular_intensity = view_dir.dot(&reflect_dir).max(0.0).powf(intersect.material.specular);
-        let specular = light.color * intersect.material.albedo[1] * specular_intensity * light_intensity;
-
-        total_light = total_light + diffuse + specular;
-    }
-
-    // Si el material es emisivo, añadir su contribución
-    let mut emission = intersect.material.emission * 0.5;
-
-    // Si el material es emisivo
-    if emission.r() > 0 || emission.g() > 0 || emission.b() > 0 {
-        // Aquí va el código actualizado
-        let warm_tone = Color::new(255, 180, 100); // Un tono cálido más anaranjado
-
-        // Calcula la distancia desde el punto de intersección al origen del rayo para atenuar la emisión
-        let distance = (ray_origin - intersect.point).magnitude();
-        let attenuation = 1.0 / (1.0 + 0.09 * distance + 0.032 * distance * distance); // Fórmula de atenuación
-
-        // Mezclar la emisión del glowstone con el tono cálido y reducir la intensidad
-        emission = (emission * 0.2 + warm_tone * 0.5) * attenuation; // Reduciendo la intensidad y aumentando el tono cálido
-
-        // Agregar la emisión al total de la luz reflejada
-        total_light = total_light + emission;
+        let shadow = cast_shadow(&intersect, light, objects, ray.time, clock);
+        let radiance = color_to_vec(light.color).component_mul(&(Vec3::new(1.0, 1.0, 1.0) - shadow)) * light.intensity;
+
+        // albedo[0] y albedo[1] siguen ponderando difuso y especular (valen 1 en los materiales PBR)
+        let (diffuse, specular) = ggx_brdf(
+            &intersect.normal,
+            &view_dir,
+            &light_dir,
+            &base_color,
+            material.refractive_index,
+            &pbr,
+        );
+        let reflected = diffuse * material.albedo[0] + specular * material.albedo[1];
+
+        // Las luces puntuales se escalan por π para que una superficie blanca de Lambert devuelva la intensidad de la luz
+        total_light = total_light + vec_to_color(&(reflected.component_mul(&radiance) * (n_dot_l * PI)));
     }
 
+    // Luz propia del material; con mapa de emisión solo brillan sus texeles claros
+    let emission = material.get_emission(intersect.face, intersect.u, intersect.v, footprint, clock);
+    total_light = total_light + vec_to_color(&emission);
 
-    // Cálculo del factor de Fresnel
-    let cos_theta = -intersect.normal.dot(ray_direction).max(-1.0).min(1.0);
-    let r0 = ((1.0 - intersect.material.refractive_index) / (1.0 + intersect.material.refractive_index)).powi(2);
-    let fresnel_reflectance = (r0 + (1.0 - r0) * (1.0 - cos_theta).powi(5)).clamp(0.0, 1.0);
+    // Cálculo del factor de Fresnel; en los metales la reflectancia base es su propio color
+    #[allow(clippy::manual_clamp)]
+    let cos_theta = -intersect.normal.dot(&ray.direction).max(-1.0).min(1.0);
+    let f0 = base_reflectance(&base_color, material.refractive_index, pbr.metallic);
+    let fresnel = fresnel_schlick(cos_theta, &f0);
+    let fresnel_reflectance = fresnel.max().clamp(0.0, 1.0);
+    let reflectivity = material.reflectivity(&pbr);
 
     // Ajustar reflectividad con Fresnel
     let mut reflect_color = Color::black();
-    if intersect.material.albedo[2] > 0.0 {
-        let reflect_dir = reflect(&ray_direction, &intersect.normal).normalize();
+    if reflectivity > 0.0 {
+        let reflect_dir = reflect(&ray.direction, &intersect.normal).normalize();
         let reflect_origin = offset_point(&intersect, &reflect_dir);
-        reflect_color = cast_ray(&reflect_origin, &reflect_dir, objects, lights, skybox, depth + 1);
+        let reflect_ray = Ray::new(reflect_origin, reflect_dir)
+            .with_time(ray.time)
+            .with_cone(ray.footprint(intersect.distance), ray.cone_spread);
+        let reflected = color_to_vec(cast_ray(&reflect_ray, objects, lights, skybox, clock, depth + 1, medium));
+        // El reflejo se tiñe según el Fresnel de cada canal (un metal dorado refleja dorado)
+        reflect_color = vec_to_color(&(reflected.component_mul(&fresnel) / fresnel_reflectance.max(1e-4)));
     }
 
     // Ajustar transparencia con Fresnel
     let mut refract_color = Color::black();
     if intersect.material.albedo[3] > 0.0 {
-        let refract_dir = refract(&ray_direction, &intersect.normal, intersect.material.refractive_index);
+        let refract_dir = refract(
+            &ray.direction,
+            &intersect.normal,
+            intersect.material.refractive_index,
+            intersect.front_face,
+        );
         let refract_origin = offset_point(&intersect, &refract_dir);
-        refract_color = cast_ray(&refract_origin, &refract_dir, objects, lights, skybox, depth + 1);
+        let refract_ray = Ray::new(refract_origin, refract_dir)
+            .with_time(ray.time)
+            .with_cone(ray.footprint(intersect.distance), ray.cone_spread);
+        // Al entrar queda dentro del material y al salir vuelve al aire; con reflexión total
+        // interna sigue en el mismo medio
+        let refract_medium = if refract_dir.dot(&intersect.normal) > 0.0 {
+            medium
+        } else if intersect.front_face {
+            Some(material)
+        } else {
+            None
+        };
+        refract_color = cast_ray(&refract_ray, objects, lights, skybox, clock, depth + 1, refract_medium);
     }
 
     // Incorporar Fresnel en reflectividad y transparencia
-    let final_reflectivity = fresnel_reflectance * intersect.material.albedo[2];
+    let final_reflectivity = fresnel_reflectance * reflectivity;
     let final_transparency = (1.0 - fresnel_reflectance) * intersect.material.albedo[3];
 
     // Ajustar los valores de reflectividad y transparencia para asegurar que no excedan el rango permitido
-    let scaling_factor = 1.0 / (final_reflectivity + final_transparency + (1.0 - intersect.material.albedo[2] - intersect.material.albedo[3]));
+    let scaling_factor = 1.0 / (final_reflectivity + final_transparency + (1.0 - reflectivity - intersect.material.albedo[3]));
 
     let final_color = (total_light) * (1.0 - final_reflectivity - final_transparency) * scaling_factor +
         (reflect_color * final_reflectivity) * scaling_factor +
         (refract_color * final_transparency) * scaling_factor;
 
+    // Si el rayo entró al material en su origen y sale por esta cara, lo que trae se atenúa con el
+    // espesor recorrido (Beer–Lambert); se exige el mismo material, no uno igual de otro bloque.
+    // Las superficies de una sola cara solo usan albedo[3]
+    let final_color = match medium {
+        Some(entered) if !intersect.front_face && std::ptr::eq(entered, material) => {
+            let absorbed = material.transmittance(intersect.distance * ray.direction.magnitude());
+            vec_to_color(&color_to_vec(final_color).component_mul(&absorbed))
+        }
+        _ => final_color,
+    };
+
+    // Con alfa mezclado la superficie solo cubre una parte; el resto es lo que hay detrás
+    let final_color = if coverage < 1.0 {
+        let behind = match past_hit(ray, &intersect) {
+            Some(next) => cast_ray(&next, objects, lights, skybox, clock, depth, medium),
+            None => SKYBOX_COLOR,
+        };
+        final_color * coverage + behind * (1.0 - coverage)
+    } else {
+        final_color
+    };
+
+    #[allow(clippy::needless_return)]
     return clamp_color(final_color);
 }
 
 
-pub fn render(framebuffer: &mut Framebuffer, objects: &[Cube], camera: &Camera, lights: &[Light]) {
+// `clock` son los segundos de la escena: decide el cuadro de las texturas animadas
+pub fn render(
+    framebuffer: &mut Framebuffer,
+    objects: &Scene,
+    camera: &Camera,
+    lights: &[Light],
+    skybox: &Texture,
+    clock: f32,
+) {
     let width = framebuffer.width as f32;
     let height = framebuffer.height as f32;
     let aspect_ratio = width / height;
     let fov = PI / 3.0;
     let perspective_scale = (fov / 2.0).tan();
-    let skybox_texture = Arc::new(Texture::new("assets\\sky.png"));
+    // Ángulo que abarca un píxel: el cono de cada rayo crece con él para elegir el mipmap
+    let pixel_spread = 2.0 * perspective_scale / height;
 
     let pixels: Vec<_> = (0..framebuffer.height).flat_map(|y| {
         (0..framebuffer.width).map(move |x| (x, y))
@@ -250,7 +443,27 @@ pub fn render(framebuffer: &mut Framebuffer, objects: &[Cube], camera: &Camera,
         let screen_y = screen_y * perspective_scale;
         let ray_direction = Vec3::new(screen_x, screen_y, -1.0).normalize();
         let rotated_direction = camera.basis_change(&ray_direction);
-        let pixel_color = cast_ray(&camera.eye, &rotated_direction, objects, lights, &skybox_texture, 0);
+
+        let ray = Ray::new(camera.eye, rotated_direction).with_cone(0.0, pixel_spread);
+        let samples = camera.shutter_samples.max(1);
+        let pixel_color = if samples == 1 {
+            // Sin desenfoque basta una muestra, en la mitad del obturador
+            cast_ray(&ray.with_time(0.5), objects, lights, skybox, clock, 0, None)
+        } else {
+            // Cada muestra cae en un instante distinto del obturador (estratificado con jitter)
+            // para que los objetos en movimiento se vean desenfocados
+            let mut rng = rand::thread_rng();
+            let (mut r, mut g, mut b) = (0.0, 0.0, 0.0);
+            for sample in 0..samples {
+                let time = (sample as f32 + rng.gen::<f32>()) / samples as f32;
+                let color = cast_ray(&ray.with_time(time), objects, lights, skybox, clock, 0, None);
+                r += color.r() as f32;
+                g += color.g() as f32;
+                b += color.b() as f32;
+            }
+            let n = samples as f32;
+            Color::new((r / n).round() as u8, (g / n).round() as u8, (b / n).round() as u8)
+        };
         (x, y, pixel_color.to_hex())
     }).collect();
 
@@ -289,9 +502,12 @@ fn main() {
 
     //Emisiones
     let glowstone_emission = Color::new(255, 223, 127); // Color de la luz que emite
+    let magma_emission = Color::new(255, 140, 60);
 
     //Texturas
-    let grass_texture = Arc::new(Texture::new("assets\\grass.png"));
+    let grass_top_texture = Arc::new(Texture::new("assets\\grass_block_top.png")); // En gris: lo tiñe el bioma
+    let grass_side_texture = Arc::new(Texture::new("assets\\grass_block_side.png"));
+    let grass_bottom_texture = Arc::new(Texture::new("assets\\grass_block_bottom.png"));
     let dirt_texture = Arc::new(Texture::new("assets\\dirt.png"));
     let iron_texture = Arc::new(Texture::new("assets\\iron_ore.png"));
     let gold_texture = Arc::new(Texture::new("assets\\gold_ore.png"));
@@ -304,13 +520,26 @@ fn main() {
     let obsidian_texture = Arc::new(Texture::new("assets\\obsidian.png"));
     let chiseled_stone_texture = Arc::new(Texture::new("assets\\chiseled_stone.png"));
     let gold_block_texture = Arc::new(Texture::new("assets\\gold_block.png"));
-    let magma_texture = Arc::new(Texture::new("assets\\magma.png"));
+    let magma_texture = Arc::new(Texture::new("assets\\magma_animated.png"));
     let stone_bricks_texture = Arc::new(Texture::new("assets\\stone_bricks.png"));
     let glowstone_texture = Arc::new(Texture::new("assets\\glowstone.png"));
     let stone_texture: Arc<Texture> = Arc::new(Texture::new("assets\\stone.png"));
     let netherrack_texture: Arc<Texture> = Arc::new(Texture::new("assets\\netherrack.png"));
-
-    let grass_material = Material::new_with_texture(0.1, [0.85, 0.1, 0.05, 0.0], 1.3, grass_texture.clone());
+    let ball_texture = Arc::new(Texture::new("assets\\ball.png"));
+    let ball_normal_map = Arc::new(Texture::new("assets\\ball_normal.png"));
+
+    // --biome=plains|forest|jungle|desert|savanna|taiga|snowy_plains elige el color del pasto
+    let biome_name = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--biome=").map(str::to_string))
+        .unwrap_or_else(|| "plains".to_string());
+    let biome = Biome::named(&biome_name).unwrap_or_else(|| panic!("Unknown biome: {}", biome_name));
+    let grass_material = Material::new_with_face_textures(
+        0.1,
+        [0.85, 0.1, 0.05, 0.0],
+        1.3,
+        Faces::side(grass_top_texture, grass_bottom_texture, grass_side_texture),
+    )
+    .with_tint_indices(Faces::side(Some(TINT_GRASS), None, None));
     let dirt_material = Material::new_with_texture(0.2, [0.9, 0.05, 0.05, 0.0], 1.0, dirt_texture);
     let iron_material = Material::new_with_texture(0.3, [0.6, 0.1, 0.0, 0.0], 1.5, iron_texture);  
     let gold_material = Material::new_with_texture(0.5, [0.6, 0.1, 0.0, 0.0], 1.3, gold_texture);  
@@ -325,12 +554,29 @@ fn main() {
     let obsidian_material = Material::new_with_texture(0.1, [0.7, 0.3, 0.04, 0.0], 1.5, obsidian_texture);
     let chiseled_stone_material = Material::new_with_texture(0.1, [0.6, 0.05, 0.0, 0.0], 1.5, chiseled_stone_texture);
     let gold_block_material = Material::new_with_texture(0.1, [0.85, 0.5, 0.05, 0.0], 0.47, gold_block_texture);
-    let magma_material = Material::new_with_texture(0.2, [0.7, 0.5, 0.03, 0.0], 1.5, magma_texture);
+    // Solo brillan las grietas claras del magma; el glowstone brilla en todos sus texeles
+    let magma_material = Material::new_with_texture_and_emission(0.2, [0.7, 0.5, 0.03, 0.0], 1.5, magma_emission, magma_texture.clone())
+        .with_emission_map(Arc::new(magma_texture.emissive_mask(0.45)));
     let stone_bricks_material = Material::new_with_texture(0.1, [0.6, 0.05, 0.0, 0.0], 1.5, stone_bricks_texture);
 
-    let glowstone_material = Material::new_with_texture_and_emission(0.2, [0.9, 0.1, 0.0, 0.0], 1.2, glowstone_emission, glowstone_texture);
+    let glowstone_material = Material::new_with_texture_and_emission(0.2, [0.9, 0.1, 0.0, 0.0], 1.2, glowstone_emission, glowstone_texture.clone())
+        .with_emission_map(glowstone_texture);
     let stone_material = Material::new_with_texture(0.1, [0.6, 0.05, 0.0, 0.0], 1.5, stone_texture);
     let netherrack_material = Material::new_with_texture(0.1, [0.8, 0.1, 0.1, 0.0], 1.0, netherrack_texture);
+    let ball_material = Material::new_with_texture(20.0, [0.8, 0.3, 0.1, 0.0], 1.3, ball_texture)
+        .with_normal_map(ball_normal_map);
+    // Mármol pulido sin archivos: el color y la rugosidad son patrones procedurales
+    let marble_material = Material::new_pbr_with_texture(
+        0.0,
+        0.5,
+        1.5,
+        Color::black(),
+        Procedural::marble(2.5, 1.2, Color::new(236, 233, 226), Color::new(84, 86, 96)),
+    )
+    .with_roughness_map(Procedural::noise(6.0, 4, Color::new(80, 80, 80), Color::new(255, 255, 255)));
+    // Hielo transparente: se ve más azul cuanto más espesor atraviesa la luz, y su sombra también es azul
+    let ice_material = Material::new(Color::new(210, 230, 255), 80.0, [0.2, 0.4, 0.1, 0.8], 1.31, Color::black())
+        .with_absorption_color(Color::new(120, 180, 240), 1.0);
 
     // Materiales al lado del portal
     let materials = [stone_material, stone_bricks_material, chiseled_stone_material];
@@ -342,6 +588,7 @@ fn main() {
             let mut place_dirt = true;  // Asumimos que se coloca tierra a menos que se especifique lo contrario
 
             // Especificar filas y columnas que tendrán un material diferente
+            #[allow(clippy::manual_range_contains, clippy::single_match)]
             if (i == 5 && (j == 2 || j == 3 || j == 6)) || (i == 4 && (j >= 1 && j <= 6)) {
                 material = netherrack_material.clone();
                 place_dirt = true;  
@@ -380,10 +627,12 @@ fn main() {
             }
 
             // Colocar los bloques con el material especificado o el default
+            #[allow(clippy::redundant_field_names)]
             objects.push(Cube {
                 min: Vec3::new(i as f32, 1.0, j as f32),
                 max: Vec3::new(i as f32 + 1.0, 2.0, j as f32 + 1.0),
                 material: material,
+                motion: None,
             });
 
             // Agregar la capa de tierra debajo si es necesario
@@ -392,6 +641,7 @@ fn main() {
                     min: Vec3::new(i as f32, 0.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 1.0, j as f32 + 1.0),
                     material: dirt_material.clone(),
+                    motion: None,
                 });
             }
     
@@ -404,10 +654,12 @@ fn main() {
                     6 => coal_material.clone(),  // Bloque de carbón
                     _ => grass_material.clone(), // Este caso no debería ocurrir
                 };
+                #[allow(clippy::redundant_field_names)]
                 objects.push(Cube {
                     min: Vec3::new(i as f32, 2.0, j as f32), // Estos bloques van encima de la grama
                     max: Vec3::new(i as f32 + 1.0, 3.0, j as f32 + 1.0),
                     material: material,
+                    motion: None,
                 });
             }
 
@@ -417,6 +669,7 @@ fn main() {
                         min: Vec3::new(i as f32, 2.0 + k as f32, j as f32),
                         max: Vec3::new(i as f32 + 1.0, 3.0 + k as f32, j as f32 + 1.0),
                         material: bookshelf_material.clone(),
+                        motion: None,
                     });
                 }
             }
@@ -427,6 +680,7 @@ fn main() {
                     min: Vec3::new(i as f32, 2.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 3.0 , j as f32 + 1.0),
                     material: bookshelf_material.clone(),
+                    motion: None,
                 });
             }
         
@@ -437,6 +691,7 @@ fn main() {
                     min: Vec3::new(i as f32, 2.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 3.0, j as f32 + 1.0),
                     material: crafting_table_material.clone(),
+                    motion: None,
                 });
                 
             }
@@ -445,6 +700,7 @@ fn main() {
                     min: Vec3::new(i as f32, 2.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 3.0, j as f32 + 1.0),
                     material: furnance_material.clone(),
+                    motion: None,
                 });
                 
             }
@@ -456,6 +712,7 @@ fn main() {
                         min: Vec3::new(i as f32, 2.0 + k as f32, j as f32),
                         max: Vec3::new(i as f32 + 1.0, 3.0 + k as f32, j as f32 + 1.0),
                         material: crying_obsidian_material.clone(),
+                        motion: None,
                     });
                 }
                 
@@ -466,6 +723,7 @@ fn main() {
                     min: Vec3::new(i as f32, 2.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 3.0, j as f32 + 1.0),
                     material: obsidian_material.clone(),
+                    motion: None,
                 });
             }
 
@@ -475,6 +733,7 @@ fn main() {
                     min: Vec3::new(i as f32, 2.0, j as f32),
                     max: Vec3::new(i as f32 + 1.0, 3.0, j as f32 + 1.0),
                     material: crying_obsidian_material.clone(),
+                    motion: None,
                 });
 
                 // Agregar el glowstone encima del bloque de crying obsidian
@@ -482,6 +741,7 @@ fn main() {
                     min: Vec3::new(i as f32, 3.0, j as f32), // Una capa arriba del crying obsidian
                     max: Vec3::new(i as f32 + 1.0, 4.0, j as f32 + 1.0),
                     material: glowstone_material.clone(), // Usar el material de glowstone
+                    motion: None,
                 });
             }
 
@@ -492,6 +752,7 @@ fn main() {
                         min: Vec3::new(i as f32, 2.0 + k as f32, j as f32),
                         max: Vec3::new(i as f32 + 1.0, 3.0 + k as f32, j as f32 + 1.0),
                         material: obsidian_material.clone(),
+                        motion: None,
                     });
                 }
             }
@@ -503,11 +764,28 @@ fn main() {
                     min: Vec3::new(6.0, 2.0 + k, 5.0),
                     max: Vec3::new(7.0, 3.0 + k, 6.0),
                     material: material.clone(),
+                    motion: None,
                 });
             }
         }
     }    
 
+    // Bloque de mármol sobre el bloque de oro
+    objects.push(Cube {
+        min: Vec3::new(3.0, 2.0, 2.0),
+        max: Vec3::new(4.0, 3.0, 3.0),
+        material: marble_material,
+        motion: None,
+    });
+
+    // Bloque de hielo junto a la pelota
+    objects.push(Cube {
+        min: Vec3::new(3.0, 2.0, 4.0),
+        max: Vec3::new(4.0, 3.0, 5.0),
+        material: ice_material,
+        motion: None,
+    });
+
     // Configuración de la cámara
     let mut camera = Camera::new(
         Vec3::new(-5.0, 5.0, -10.0), // Posición de la cámara ajustada
@@ -515,26 +793,239 @@ fn main() {
         Vec3::new(0.0, 1.0, 0.0),   // Vector "up" de la cámara
     );
 
+    // --shutter-samples=N reparte N muestras por píxel a lo largo del obturador (desenfoque de movimiento)
+    camera.shutter_samples = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--shutter-samples=").map(str::to_string))
+        .map_or(1, |count| count.parse().unwrap_or_else(|_| panic!("Invalid shutter sample count: {}", count)));
+
     // Crear una lista de luces que incluirá la luz principal y las luces de los bloques glowstone
     let mut lights: Vec<Light> = vec![
         Light::new(Vec3::new(-5.0, 10.0, -10.0), Color::new(255, 255, 255), 1.0), // Luz principal
     ];
 
-    // Ahora recorremos todos los objetos y añadimos los bloques de glowstone como fuentes de luz
+    // Ahora recorremos todos los objetos y añadimos los bloques emisivos como fuentes de luz,
+    // con el color y la intensidad medios de lo que emiten sus texeles
     for object in &objects {
-        if object.material.emission.r() > 0 || object.material.emission.g() > 0 || object.material.emission.b() > 0 {
+        let emission = object.material.average_emission();
+        let intensity = emission.max();
+        if intensity > 0.0 {
             lights.push(Light::new(
-                object.min + Vec3::new(0.5, 0.5, 0.5), // Centro del bloque glowstone
-                object.material.emission,
-                0.8, // Ajusta la intensidad para las luces glowstone
+                (object.min + object.max) * 0.5, // Centro del bloque
+                vec_to_color(&(emission / intensity)),
+                intensity,
             ));
         }
     }
+
+    // --filter=nearest|bilinear|trilinear elige cómo se leen las texturas de todos los bloques
+    let filter = std::env::args()
+        .find_map(|arg| arg.strip_prefix("--filter=").map(str::to_string))
+        .map_or(Filter::Nearest, |name| match name.as_str() {
+            "nearest" => Filter::Nearest,
+            "bilinear" => Filter::Bilinear,
+            "trilinear" => Filter::Trilinear,
+            _ => panic!("Unknown texture filter: {}", name),
+        });
+    for object in objects.iter_mut() {
+        object.material.filter = filter;
+    }
+
+    // Todo el diorama está en el mismo bioma: las caras con índice de tinte toman sus colores
+    let biome_colors = BiomeColors::vanilla();
+    let objects: Vec<Cube> = objects.into_iter().map(|cube| cube.with_biome(&biome_colors, &biome)).collect();
+
+    // Construir la escena una sola vez; las consultas de cast_ray y cast_shadow recorren su BVH.
+    // Con --voxel-grid los bloques van en una rejilla (DDA) en vez de un cubo por bloque;
+    // con --merge-faces se quitan los bloques enterrados y se fusionan las caras visibles
+    let use_voxel_grid = std::env::args().any(|arg| arg == "--voxel-grid");
+    let merge_faces = std::env::args().any(|arg| arg == "--merge-faces");
+    let mut scene_objects: Vec<SceneObject> = if use_voxel_grid {
+        vec![Box::new(VoxelGrid::from_cubes(&objects))]
+    } else if merge_faces {
+        let (merged, stats) = merge_blocks(objects);
+        println!(
+            "Merged {} blocks into {} primitives ({} hidden blocks, {} visible faces, {} primitives saved)",
+            stats.blocks,
+            stats.primitives,
+            stats.hidden_blocks,
+            stats.visible_faces,
+            stats.saved()
+        );
+        merged
+    } else {
+        objects.into_iter().map(|cube| Box::new(cube) as SceneObject).collect()
+    };
+
+    // --ball agrega una pelota sobre la grama (una esfera con mapa de normales entre los bloques)
+    if std::env::args().any(|arg| arg == "--ball") {
+        scene_objects.push(Box::new(Sphere {
+            center: Vec3::new(2.5, 2.5, 5.5),
+            radius: 0.5,
+            material: ball_material.clone().with_filter(filter),
+            motion: None,
+        }));
+    }
+
+    // Con desenfoque de movimiento, una pelota rueda un bloque hacia +X mientras el obturador está abierto
+    if camera.shutter_samples > 1 {
+        let rolling = Motion::new(
+            Pose::identity(),
+            // Gira lo que avanza sobre su radio (1 / 0.5) alrededor del eje en que rueda
+            Pose::identity().translate(Vec3::new(1.0, 0.0, 0.0)).rotate(-2.0, Vec3::new(0.0, 0.0, 1.0)),
+        );
+        scene_objects.push(Box::new(Sphere {
+            center: Vec3::new(3.5, 2.5, 6.5),
+            radius: 0.5,
+            material: ball_material.with_filter(filter),
+            motion: Some(rolling),
+        }));
+    }
+
+    // --flat-shapes agrega un piso infinito de tierra (Plane), un vidrio delante del diorama (Quad)
+    // y un espejo dorado redondo en el suelo (Disk)
+    if std::env::args().any(|arg| arg == "--flat-shapes") {
+        let glass_material = Material::new(Color::new(200, 225, 255), 125.0, [0.1, 0.4, 0.1, 0.7], 1.5, Color::black());
+        let mirror_material = Material::new(Color::new(255, 215, 100), 200.0, [0.3, 0.8, 0.6, 0.0], 1.0, Color::black());
+        scene_objects.push(Box::new(Plane::new(
+            Vec3::new(0.0, -0.01, 0.0), // Apenas debajo de los bloques, para no pelear con sus caras
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            dirt_material.with_filter(filter),
+        )));
+        scene_objects.push(Box::new(Quad::new(
+            Vec3::new(0.0, 0.0, -1.5),
+            Vec3::new(3.0, 0.0, 0.0),
+            Vec3::new(0.0, 2.0, 0.0),
+            glass_material,
+        )));
+        scene_objects.push(Box::new(Disk::new(
+            Vec3::new(-1.5, 0.0, 4.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            1.0,
+            mirror_material,
+        )));
+    }
+
+    // --csg agrega dos bloques tallados en la última fila: un cuenco (bloque menos una esfera) con
+    // una pelota de oro adentro (unión) y un bloque redondeado (intersección de bloque y esfera)
+    if std::env::args().any(|arg| arg == "--csg") {
+        let block = |min: Vec3, material: &Material| -> SceneObject {
+            Box::new(Cube {
+                min,
+                max: min + Vec3::new(1.0, 1.0, 1.0),
+                material: material.clone().with_filter(filter),
+                motion: None,
+            })
+        };
+        let ball = |center: Vec3, radius: f32, material: &Material| -> SceneObject {
+            Box::new(Sphere {
+                center,
+                radius,
+                material: material.clone().with_filter(filter),
+                motion: None,
+            })
+        };
+
+        let bowl = Csg::new(
+            CsgOperation::Difference,
+            block(Vec3::new(7.0, 2.0, 1.0), &materials[0]),
+            ball(Vec3::new(7.5, 3.0, 1.5), 0.45, &materials[0]),
+        );
+        scene_objects.push(Box::new(Csg::new(
+            CsgOperation::Union,
+            Box::new(bowl),
+            ball(Vec3::new(7.5, 2.8, 1.5), 0.2, &gold_block_material),
+        )));
+        scene_objects.push(Box::new(Csg::new(
+            CsgOperation::Intersection,
+            block(Vec3::new(7.0, 2.0, 3.0), &materials[1]),
+            ball(Vec3::new(7.5, 2.5, 3.5), 0.68, &materials[1]),
+        )));
+    }
+
+    // --partial-blocks pone sobre la primera fila una muestra de bloques que no llenan la celda:
+    // losas abajo y arriba, escaleras hacia cada lado, una cerca unida al bloque de carbón y un muro
+    if std::env::args().any(|arg| arg == "--partial-blocks") {
+        let bricks = &materials[1];
+        let shapes = [
+            (BlockShape::Slab(Half::Bottom), bricks),
+            (BlockShape::Slab(Half::Top), bricks),
+            (BlockShape::Stairs { facing: Facing::North, half: Half::Bottom }, bricks),
+            (BlockShape::Stairs { facing: Facing::South, half: Half::Bottom }, bricks),
+            (BlockShape::Stairs { facing: Facing::East, half: Half::Top }, bricks),
+            (BlockShape::Stairs { facing: Facing::West, half: Half::Top }, bricks),
+            (BlockShape::Fence(Connections { east: true, south: true, ..Connections::default() }), &crafting_table_material),
+            (BlockShape::Wall(Connections { north: true, ..Connections::default() }), bricks),
+        ];
+        for (z, (shape, material)) in shapes.into_iter().enumerate() {
+            let material = material.clone().with_filter(filter);
+            scene_objects.push(Box::new(PartialBlock::new(Vec3::new(0.0, 2.0, z as f32), shape, material)));
+        }
+    }
+
+    // --sdf agrega en la última fila formas trazadas por su función de distancia: un pedestal de
+    // aristas redondeadas con un anillo fundido con una esfera, y una hilera de cuentas de oro
+    if std::env::args().any(|arg| arg == "--sdf") {
+        let center = Vec3::new(7.5, 2.0, 6.0);
+        let pedestal = Sdf::cuboid(Vec3::new(0.35, 0.1, 0.35)).round(0.05).translate(center + Vec3::new(0.0, 0.15, 0.0));
+        let ornament = Sdf::torus(0.25, 0.05)
+            .smooth_union(Sdf::sphere(0.15), 0.1)
+            .translate(center + Vec3::new(0.0, 0.45, 0.0));
+        scene_objects.push(Box::new(SdfObject::new(
+            pedestal.union(ornament),
+            Aabb::new(Vec3::new(7.0, 2.0, 5.5), Vec3::new(8.0, 3.0, 6.5)),
+            materials[2].clone().with_filter(filter),
+        )));
+
+        // La repetición es infinita; la caja deja solo tres cuentas
+        let beads = Sdf::sphere(0.12)
+            .repeat(Vec3::new(0.25, 100.0, 100.0))
+            .translate(Vec3::new(0.0, 2.12, 7.5));
+        scene_objects.push(Box::new(SdfObject::new(
+            beads,
+            Aabb::new(Vec3::new(7.1, 2.0, 7.35), Vec3::new(7.9, 2.25, 7.65)),
+            gold_block_material.clone().with_filter(filter),
+        )));
+    }
+
+    // --terrain=blocky|smooth agrega lomas de pasto detrás del diorama, a partir de un mapa de alturas
+    // en gris: en columnas de bloques o como una superficie de triángulos
+    if let Some(name) = std::env::args().find_map(|arg| arg.strip_prefix("--terrain=").map(str::to_string)) {
+        let mode = match name.as_str() {
+            "blocky" => HeightfieldMode::Blocky,
+            "smooth" => HeightfieldMode::Smooth,
+            _ => panic!("Unknown terrain mode: {}", name),
+        };
+        let terrain = Heightfield::from_image(
+            "assets\\terrain_height.png",
+            Vec3::new(-4.0, 0.0, 9.0),
+            1.0,
+            4.0,
+            mode,
+            grass_material.with_tints(biome_colors.tints(&biome)).with_filter(filter),
+        );
+        scene_objects.push(Box::new(terrain));
+    }
+
+    // --obj=ruta agrega un modelo Wavefront OBJ con sus materiales MTL, en coordenadas del diorama
+    // (p. ej. --obj=assets/pyramid.obj)
+    if let Some(path) = std::env::args().find_map(|arg| arg.strip_prefix("--obj=").map(str::to_string)) {
+        let mesh = load_obj(&path);
+        println!("Loaded {} triangles from {}", mesh.triangles().len(), path);
+        scene_objects.push(Box::new(mesh));
+    }
+
+    let scene = Scene::new(scene_objects);
+    // El cielo se carga una sola vez: cada carga arma los mipmaps y busca su .mcmeta
+    let skybox = Texture::new("assets\\sky.png");
     
     let rotation_speed = PI / 50.0;
     let movement_speed = 0.1;
     let zoom_speed = 0.5;
 
+    // Reloj de la escena: las texturas animadas avanzan con el tiempo real
+    let start = Instant::now();
+
     while window.is_open() {
         // listen to inputs
         if window.is_key_down(Key::Escape) {
@@ -582,7 +1073,7 @@ fn main() {
         }
 
         framebuffer.clear();
-        render(&mut framebuffer, &objects, &mut camera, &lights);
+        render(&mut framebuffer, &scene, &camera, &lights, &skybox, start.elapsed().as_secs_f32());
 
         window
             .update_with_buffer(&framebuffer.buffer, framebuffer_width, framebuffer_height)
@@ -592,3 +1083,4 @@ fn main() {
     }
 }
 
+
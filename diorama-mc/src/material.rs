@@ -1,21 +1,156 @@
+use crate::atlas::{AtlasTile, TextureAtlas};
 use crate::color::Color;
-use crate::texture::Texture;
+use crate::cube::Face;
+use crate::pbr::{color_to_vec, vec_to_color, Pbr};
+use crate::procedural::Procedural;
+use crate::texture::{Filter, Texture, Wrap};
+use nalgebra_glm::Vec3;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+// Cómo se reparte la textura sobre las caras de un bloque
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UvMapping {
+    Cross,   // Una sola imagen con las seis caras en cruz (3x4)
+    PerFace, // Una textura por cara con UV de 0 a 1, como los modelos de bloque de Minecraft
+}
+
+// Cómo se usa el alfa de la textura
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,      // Se ignora el alfa
+    Cutout(f32), // Agujeros donde el alfa no llega al umbral (hojas, flores, pasto)
+    Blend,       // El alfa mezcla la superficie con lo que hay detrás (vidrio teñido, hielo)
+}
+
+// De dónde salen los colores de un canal del material (color, emisión, rugosidad)
+#[derive(Debug, Clone)]
+pub enum TextureSource {
+    Image(Arc<Texture>),
+    Procedural(Procedural), // Calculada al vuelo a partir de la UV, sin archivo
+}
+
+impl From<Arc<Texture>> for TextureSource {
+    fn from(texture: Arc<Texture>) -> Self {
+        TextureSource::Image(texture)
+    }
+}
+
+impl From<Procedural> for TextureSource {
+    fn from(procedural: Procedural) -> Self {
+        TextureSource::Procedural(procedural)
+    }
+}
+
+impl TextureSource {
+    pub fn average_color(&self) -> Color {
+        match self {
+            TextureSource::Image(texture) => texture.average_color(),
+            TextureSource::Procedural(procedural) => procedural.average_color(),
+        }
+    }
+}
+
+// Un valor por cara de bloque: texturas, teselas de un atlas, nombres...
+#[derive(Debug, Clone, PartialEq)]
+pub struct Faces<T> {
+    pub up: T,
+    pub down: T,
+    pub north: T,
+    pub south: T,
+    pub east: T,
+    pub west: T,
+}
+
+pub type FaceTextures = Faces<Arc<Texture>>;
+pub type FaceTiles = Faces<AtlasTile>;
+
+impl<T: Clone> Faces<T> {
+    pub fn new(up: T, down: T, north: T, south: T, east: T, west: T) -> Self {
+        Faces {
+            up,
+            down,
+            north,
+            south,
+            east,
+            west,
+        }
+    }
+
+    // Lo mismo en todas las caras (piedra, tierra, tablones...)
+    pub fn all(value: T) -> Self {
+        Faces::side(value.clone(), value.clone(), value)
+    }
+
+    // Arriba, abajo y un valor compartido por los cuatro lados (césped, mesa de trabajo...)
+    pub fn side(top: T, bottom: T, side: T) -> Self {
+        Faces::new(top, bottom, side.clone(), side.clone(), side.clone(), side)
+    }
+
+    // Los dos extremos iguales (troncos, pilares)
+    pub fn column(end: T, side: T) -> Self {
+        Faces::side(end.clone(), end, side)
+    }
+
+    pub fn get(&self, face: Face) -> &T {
+        match face {
+            Face::Up => &self.up,
+            Face::Down => &self.down,
+            Face::North => &self.north,
+            Face::South => &self.south,
+            Face::East => &self.east,
+            Face::West => &self.west,
+        }
+    }
+
+    pub fn map<U: Clone>(&self, f: impl Fn(&T) -> U) -> Faces<U> {
+        Faces::new(
+            f(&self.up),
+            f(&self.down),
+            f(&self.north),
+            f(&self.south),
+            f(&self.east),
+            f(&self.west),
+        )
+    }
+}
+
+// Cada material recibe un identificador propio al crearse con `Material::new`
+static NEXT_MATERIAL_ID: AtomicU64 = AtomicU64::new(0);
+
 #[derive(Debug, Clone)]
 pub struct Material {
+    // Las copias (y las variantes hechas con `with_*`) conservan el identificador: sirve para
+    // reconocer los bloques que comparten material sin comparar texturas campo por campo
+    pub id: u64,
     pub diffuse: Color,
     pub specular: f32,
     pub albedo: [f32; 4],
     pub refractive_index: f32,
     pub emission: Color,
     pub has_texture: bool,
-    pub texture: Option<Arc<Texture>>,
+    pub texture: Option<TextureSource>,
+    pub pbr: Option<Pbr>, // Si falta, se deriva de los parámetros de Phong
+    pub normal_map: Option<Arc<Texture>>, // Normales en espacio tangente (RGB = XYZ), con la misma UV que `texture`
+    pub face_textures: Option<FaceTextures>, // Si está, reemplaza a `texture` y las caras usan UV de 0 a 1
+    pub tile: Option<AtlasTile>,            // Parte de `texture` (y de `normal_map`) que usa el material
+    pub face_tiles: Option<FaceTiles>,      // Parte de cada textura de `face_textures` que usa cada cara
+    pub filter: Filter,
+    pub wrap: Wrap, // Cómo se lee la textura fuera de 0..1; con tesela se repite dentro de la tesela
+    pub alpha_mode: AlphaMode,
+    pub emission_map: Option<TextureSource>, // Multiplica a `emission` texel a texel, con la misma UV que la textura
+    pub emission_strength: f32,
+    pub roughness_map: Option<TextureSource>, // Multiplica a la rugosidad (canal en gris), con la misma UV que la textura
+    pub tint_indices: Option<Faces<Option<usize>>>, // `tintindex` de cada cara: qué color de `tints` multiplica su textura
+    pub tints: Vec<Color>,                          // Colores del bioma del bloque, por índice de tinte
+    pub absorption: Vec3, // Coeficiente de absorción por unidad de distancia dentro del material (Beer–Lambert), por canal
 }
 
 impl Material {
     pub fn new(diffuse: Color, specular: f32, albedo: [f32; 4], refractive_index: f32, emission: Color) -> Self {
         Material {
+            id: NEXT_MATERIAL_ID.fetch_add(1, Ordering::Relaxed),
             diffuse,
             specular,
             albedo,
@@ -23,6 +158,20 @@ impl Material {
             emission,
             has_texture: false,
             texture: None,
+            pbr: None,
+            normal_map: None,
+            face_textures: None,
+            tile: None,
+            face_tiles: None,
+            filter: Filter::Nearest,
+            wrap: Wrap::Clamp,
+            alpha_mode: AlphaMode::Opaque,
+            emission_map: None,
+            emission_strength: 1.0,
+            roughness_map: None,
+            tint_indices: None,
+            tints: Vec::new(),
+            absorption: Vec3::zeros(),
         }
     }
 
@@ -30,16 +179,13 @@ impl Material {
         specular: f32,
         albedo: [f32; 4],
         refractive_index: f32,
-        texture: Arc<Texture>,
+        texture: impl Into<TextureSource>,
     ) -> Self {
         Material {
-            diffuse: Color::new(255, 255, 255), // Color difuso por defecto
-            specular,
-            albedo,
-            refractive_index,
-            emission: Color::new(0, 0, 0), // Sin emisión por defecto
             has_texture: true,
-            texture: Some(texture),
+            texture: Some(texture.into()),
+            // Color difuso blanco y sin emisión por defecto
+            ..Material::new(Color::new(255, 255, 255), specular, albedo, refractive_index, Color::new(0, 0, 0))
         }
     }
 
@@ -49,41 +195,339 @@ impl Material {
         albedo: [f32; 4],
         refractive_index: f32,
         emission: Color,
-        texture: Arc<Texture>,
+        texture: impl Into<TextureSource>,
     ) -> Self {
         Material {
-            diffuse: Color::new(255, 255, 255), // Color difuso por defecto
-            specular,
-            albedo,
-            refractive_index,
-            emission,
             has_texture: true,
-            texture: Some(texture),
+            texture: Some(texture.into()),
+            // Color difuso blanco por defecto
+            ..Material::new(Color::new(255, 255, 255), specular, albedo, refractive_index, emission)
         }
     }
 
-    pub fn get_diffuse_color(&self, u: f32, v: f32) -> Color {
+    // Bloque con una textura por cara, al estilo de los modelos de Minecraft
+    pub fn new_with_face_textures(
+        specular: f32,
+        albedo: [f32; 4],
+        refractive_index: f32,
+        face_textures: FaceTextures,
+    ) -> Self {
+        Material {
+            has_texture: true,
+            face_textures: Some(face_textures),
+            ..Material::new(Color::new(255, 255, 255), specular, albedo, refractive_index, Color::new(0, 0, 0))
+        }
+    }
+
+    // Tesela `name` del atlas, con la UV de 0 a 1 de cada cara (el bloque entero repite la tesela)
+    pub fn new_with_tile(
+        specular: f32,
+        albedo: [f32; 4],
+        refractive_index: f32,
+        atlas: &TextureAtlas,
+        name: &str,
+    ) -> Self {
+        Material {
+            face_tiles: Some(Faces::all(atlas.tile(name))),
+            ..Material::new_with_face_textures(specular, albedo, refractive_index, Faces::all(atlas.texture.clone()))
+        }
+    }
+
+    // Una tesela del atlas por cara, p. ej. Faces::side("grass_top", "dirt", "grass_side")
+    pub fn new_with_face_tiles(
+        specular: f32,
+        albedo: [f32; 4],
+        refractive_index: f32,
+        atlas: &TextureAtlas,
+        names: Faces<&str>,
+    ) -> Self {
+        Material {
+            face_tiles: Some(names.map(|name| atlas.tile(name))),
+            ..Material::new_with_face_textures(specular, albedo, refractive_index, Faces::all(atlas.texture.clone()))
+        }
+    }
+
+    // Con una tesela se conserva el mapeo del material (p. ej. una cruz 3x4 empaquetada en un atlas)
+    pub fn with_tile(mut self, tile: AtlasTile) -> Self {
+        self.tile = Some(tile);
+        self
+    }
+
+    // Los patrones procedurales no tienen cruz: cada cara recibe el patrón completo
+    pub fn uv_mapping(&self) -> UvMapping {
+        if self.face_textures.is_some() || matches!(self.texture, Some(TextureSource::Procedural(_))) {
+            UvMapping::PerFace
+        } else {
+            UvMapping::Cross
+        }
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    // Sin color de emisión previo, el mapa emite su propio color
+    pub fn with_emission_map(mut self, emission_map: impl Into<TextureSource>) -> Self {
+        if self.emission.to_hex() == 0 {
+            self.emission = Color::new(255, 255, 255);
+        }
+        self.emission_map = Some(emission_map.into());
+        self
+    }
+
+    pub fn with_emission_strength(mut self, strength: f32) -> Self {
+        self.emission_strength = strength;
+        self
+    }
+
+    pub fn is_emissive(&self) -> bool {
+        self.emission.to_hex() != 0 && self.emission_strength > 0.0
+    }
+
+    // Luz que emite la superficie en el punto (RGB lineal, puede pasar de 1 con la intensidad)
+    pub fn get_emission(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> Vec3 {
+        if !self.is_emissive() {
+            return Vec3::zeros();
+        }
+        let tint = color_to_vec(self.emission) * self.emission_strength;
+        match &self.emission_map {
+            Some(map) => {
+                let (texel, _) = self.sample_source(map, self.map_tile(face), u, v, footprint, clock);
+                tint.component_mul(&color_to_vec(texel))
+            }
+            None => tint,
+        }
+    }
+
+    // Emisión media sobre toda la superficie, para convertir el material en una luz de la escena
+    pub fn average_emission(&self) -> Vec3 {
+        if !self.is_emissive() {
+            return Vec3::zeros();
+        }
+        let tint = color_to_vec(self.emission) * self.emission_strength;
+        match &self.emission_map {
+            Some(map) => tint.component_mul(&color_to_vec(map.average_color())),
+            None => tint,
+        }
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    // Color de la cara golpeada; sin cara (esferas, mallas...) se usa la cara superior.
+    // `footprint` es el ancho del píxel en unidades de UV y decide el nivel de mipmap; `clock`
+    // (segundos) elige el cuadro de las texturas animadas
+    pub fn get_face_color(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> Color {
+        self.get_face_texel(face, u, v, footprint, clock).0
+    }
+
+    // Color (ya teñido) junto con la cobertura según el modo de alfa: 0 deja pasar el rayo, 1 lo detiene
+    pub fn get_face_texel(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> (Color, f32) {
+        let (color, alpha) = self.sample_face(face, u, v, footprint, clock);
+        let color = match self.tint(face) {
+            Some(tint) => vec_to_color(&color_to_vec(color).component_mul(&color_to_vec(tint))),
+            None => color,
+        };
+        (color, self.alpha_to_coverage(alpha))
+    }
+
+    // Qué caras se tiñen y con qué índice, p. ej. Faces::side(Some(TINT_GRASS), None, None) para
+    // el pasto o Faces::all(Some(TINT_FOLIAGE)) para las hojas
+    pub fn with_tint_indices(mut self, tint_indices: Faces<Option<usize>>) -> Self {
+        self.tint_indices = Some(tint_indices);
+        self
+    }
+
+    // Colores del bioma, p. ej. BiomeColors::tints
+    pub fn with_tints(mut self, tints: Vec<Color>) -> Self {
+        self.tints = tints;
+        self
+    }
+
+    // Color que multiplica la textura de la cara; sin índice o sin color para él, no se tiñe
+    pub fn tint(&self, face: Option<Face>) -> Option<Color> {
+        let index = (*self.tint_indices.as_ref()?.get(face.unwrap_or(Face::Up)))?;
+        self.tints.get(index).copied()
+    }
+
+    // Solo la cobertura; los materiales opacos no leen la textura (rayos de sombra)
+    pub fn coverage(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> f32 {
+        match self.alpha_mode {
+            AlphaMode::Opaque => 1.0,
+            _ => self.alpha_to_coverage(self.sample_face(face, u, v, footprint, clock).1),
+        }
+    }
+
+    // Tapa por completo lo que hay detrás: sin transparencia ni alfa
+    pub fn is_opaque(&self) -> bool {
+        self.albedo[3] <= 0.0 && self.alpha_mode == AlphaMode::Opaque
+    }
+
+    fn alpha_to_coverage(&self, alpha: f32) -> f32 {
+        match self.alpha_mode {
+            AlphaMode::Opaque => 1.0,
+            AlphaMode::Cutout(threshold) => {
+                if alpha >= threshold {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            AlphaMode::Blend => alpha,
+        }
+    }
+
+    fn sample_face(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> (Color, f32) {
         if self.has_texture {
-            if let Some(tex) = &self.texture {
-                let u = u.clamp(0.0, 1.0);
-                let v = v.clamp(0.0, 1.0);
-                let x = (u * (tex.width as f32)).round() as usize;
-                let y = ((1.0 - v) * (tex.height as f32)).round() as usize;
-                return tex.get_color(x.min(tex.width - 1), y.min(tex.height - 1));
+            if let Some(faces) = &self.face_textures {
+                let face = face.unwrap_or(Face::Up);
+                let tile = self.face_tiles.as_ref().map(|tiles| tiles.get(face));
+                return self.sample(faces.get(face), tile, u, v, footprint, clock);
+            }
+            if let Some(source) = &self.texture {
+                return self.sample_source(source, self.tile.as_ref(), u, v, footprint, clock);
             }
         }
-        self.diffuse
+        (self.diffuse, 1.0)
     }
 
-    pub fn black() -> Self {
+    pub fn get_diffuse_color(&self, u: f32, v: f32) -> Color {
+        self.get_face_color(None, u, v, 0.0, 0.0)
+    }
+
+    pub fn with_normal_map(mut self, normal_map: Arc<Texture>) -> Self {
+        self.normal_map = Some(normal_map);
+        self
+    }
+
+    // Normal del mapa en espacio tangente (x sobre u, y sobre v, z hacia afuera), si hay mapa.
+    // Con teselas por cara se lee la tesela de la cara, igual que la textura
+    pub fn get_normal(&self, face: Option<Face>, u: f32, v: f32, clock: f32) -> Option<Vec3> {
+        let map = self.normal_map.as_ref()?;
+        let (texel, _) = self.sample(map, self.map_tile(face), u, v, 0.0, clock);
+        let normal = Vec3::new(texel.r() as f32, texel.g() as f32, texel.b() as f32) / 127.5
+            - Vec3::new(1.0, 1.0, 1.0);
+        Some(normal.normalize())
+    }
+
+    // Material metallic/roughness; albedo queda en [1, 1, 0, 0] para que no pondere nada
+    pub fn new_pbr(base_color: Color, metallic: f32, roughness: f32, refractive_index: f32, emission: Color) -> Self {
         Material {
-            diffuse: Color::new(0, 0, 0),
-            specular: 0.0,
-            albedo: [0.0, 0.0, 0.0, 0.0],
-            refractive_index: 0.0,
-            has_texture: false,
-            emission: Color::new(0, 0, 0),
-            texture: None,
+            pbr: Some(Pbr::new(metallic, roughness)),
+            ..Material::new(base_color, 0.0, [1.0, 1.0, 0.0, 0.0], refractive_index, emission)
+        }
+    }
+
+    pub fn new_pbr_with_texture(
+        metallic: f32,
+        roughness: f32,
+        refractive_index: f32,
+        emission: Color,
+        texture: impl Into<TextureSource>,
+    ) -> Self {
+        Material {
+            has_texture: true,
+            texture: Some(texture.into()),
+            ..Material::new_pbr(Color::new(255, 255, 255), metallic, roughness, refractive_index, emission)
+        }
+    }
+
+    // Lectura con el filtro del material; la tesela reduce la UV y con ella el ancho del píxel
+    fn sample(&self, tex: &Texture, tile: Option<&AtlasTile>, u: f32, v: f32, footprint: f32, clock: f32) -> (Color, f32) {
+        // La tesela es solo una parte del atlas: la repetición se hace antes de llevar la UV a ella
+        let (u, v, footprint, wrap) = match tile {
+            Some(tile) => {
+                let (u, v) = tile.remap(self.wrap.apply(u), self.wrap.apply(v));
+                (u, v, footprint * tile.extent(), Wrap::Clamp)
+            }
+            None => (u, v, footprint, self.wrap),
+        };
+        let texels = footprint * ((tex.width * tex.height) as f32).sqrt();
+        tex.sample_rgba(u, v, self.filter, wrap, texels.max(1e-6).log2(), clock)
+    }
+
+    // Parámetros con los que se sombrea; los materiales de Phong se convierten al vuelo
+    pub fn pbr(&self) -> Pbr {
+        self.pbr.unwrap_or_else(|| Pbr::from_phong(self.specular))
+    }
+
+    pub fn with_roughness_map(mut self, roughness_map: impl Into<TextureSource>) -> Self {
+        self.roughness_map = Some(roughness_map.into());
+        self
+    }
+
+    // Parámetros en el punto: el mapa de rugosidad escala la rugosidad del material
+    pub fn pbr_at(&self, face: Option<Face>, u: f32, v: f32, footprint: f32, clock: f32) -> Pbr {
+        let pbr = self.pbr();
+        match &self.roughness_map {
+            Some(map) => {
+                let (texel, _) = self.sample_source(map, self.map_tile(face), u, v, footprint, clock);
+                let roughness = color_to_vec(texel).dot(&Vec3::new(0.2126, 0.7152, 0.0722));
+                Pbr::new(pbr.metallic, pbr.roughness * roughness)
+            }
+            None => pbr,
+        }
+    }
+
+    // Tesela que usan los mapas secundarios (emisión, rugosidad) en la cara golpeada
+    fn map_tile(&self, face: Option<Face>) -> Option<&AtlasTile> {
+        match &self.face_tiles {
+            Some(tiles) => Some(tiles.get(face.unwrap_or(Face::Up))),
+            None => self.tile.as_ref(),
+        }
+    }
+
+    // Las imágenes pasan por el filtro y los mipmaps; los patrones se calculan en la UV sin remapear
+    fn sample_source(
+        &self,
+        source: &TextureSource,
+        tile: Option<&AtlasTile>,
+        u: f32,
+        v: f32,
+        footprint: f32,
+        clock: f32,
+    ) -> (Color, f32) {
+        match source {
+            TextureSource::Image(texture) => self.sample(texture, tile, u, v, footprint, clock),
+            TextureSource::Procedural(procedural) => (procedural.sample(u, v, footprint), 1.0),
+        }
+    }
+
+    // Medio que se tiñe según el espesor: la luz que recorre `d` unidades adentro se multiplica por exp(-absorption·d)
+    pub fn with_absorption(mut self, absorption: Vec3) -> Self {
+        self.absorption = absorption;
+        self
+    }
+
+    // Absorción con la que la luz blanca queda del color `color` tras recorrer `distance` unidades adentro
+    pub fn with_absorption_color(self, color: Color, distance: f32) -> Self {
+        let absorption = color_to_vec(color).map(|channel| -channel.max(1e-4).ln() / distance);
+        self.with_absorption(absorption)
+    }
+
+    // Fracción de luz por canal que sobrevive a `distance` unidades dentro del material
+    pub fn transmittance(&self, distance: f32) -> Vec3 {
+        (-self.absorption * distance.max(0.0)).map(f32::exp)
+    }
+
+    // Peso del reflejo trazado: albedo[2] en Phong; en PBR las superficies lisas reflejan más.
+    // `pbr` son los parámetros en el punto (ver `pbr_at`)
+    pub fn reflectivity(&self, pbr: &Pbr) -> f32 {
+        match self.pbr {
+            Some(_) => (1.0 - pbr.roughness) * (1.0 - pbr.roughness),
+            None => self.albedo[2],
         }
     }
+
+    pub fn black() -> Self {
+        Material::new(Color::new(0, 0, 0), 0.0, [0.0, 0.0, 0.0, 0.0], 0.0, Color::new(0, 0, 0))
+    }
 }
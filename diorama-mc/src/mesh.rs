@@ -0,0 +1,140 @@
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::{Vec2, Vec3};
+use std::sync::Arc;
+
+const EPSILON: f32 = 1e-7;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Vertex {
+    pub position: Vec3,
+    pub normal: Vec3,
+    pub uv: Vec2,
+}
+
+pub struct Triangle {
+    pub vertices: [Vertex; 3],
+    pub material: Arc<Material>, // Compartido entre todos los triángulos de un mismo material
+}
+
+impl Triangle {
+    pub fn new(vertices: [Vertex; 3], material: Arc<Material>) -> Self {
+        Triangle { vertices, material }
+    }
+
+    pub fn face_normal(&self) -> Vec3 {
+        let [a, b, c] = &self.vertices;
+        (b.position - a.position).cross(&(c.position - a.position)).normalize()
+    }
+
+    fn hit(&self, ray: &Ray) -> Option<(f32, f32, f32)> {
+        let [a, b, c] = &self.vertices;
+        triangle_hit(&a.position, &b.position, &c.position, ray)
+    }
+}
+
+// Möller–Trumbore: distancia y coordenadas baricéntricas (w1, w2) del impacto
+pub fn triangle_hit(a: &Vec3, b: &Vec3, c: &Vec3, ray: &Ray) -> Option<(f32, f32, f32)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+
+    let p = ray.direction.cross(&edge2);
+    let det = edge1.dot(&p);
+    if det.abs() < EPSILON {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin - a;
+    let w1 = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&w1) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let w2 = ray.direction.dot(&q) * inv_det;
+    if w2 < 0.0 || w1 + w2 > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    if t <= EPSILON || !ray.in_range(t) {
+        return None;
+    }
+    Some((t, w1, w2))
+}
+
+impl RayIntersect for Triangle {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let (t, w1, w2) = match self.hit(ray) {
+            Some(hit) => hit,
+            None => return Intersect::empty(),
+        };
+
+        // Interpolar normal y UV con las coordenadas baricéntricas
+        let [a, b, c] = &self.vertices;
+        let w0 = 1.0 - w1 - w2;
+        let point = ray.at(t);
+        let mut normal = a.normal * w0 + b.normal * w1 + c.normal * w2;
+        if normal.magnitude_squared() < EPSILON {
+            normal = self.face_normal();
+        } else {
+            normal = normal.normalize();
+        }
+        let uv = a.uv * w0 + b.uv * w1 + c.uv * w2;
+
+        let mut intersect = Intersect::new(point, normal, t, &self.material, uv.x, uv.y);
+        // El lado se decide con la normal geométrica; la interpolada solo se invierte
+        if ray.direction.dot(&self.face_normal()) > 0.0 {
+            intersect.front_face = false;
+            intersect.normal = -normal;
+        }
+        intersect
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.hit(ray).map(|_| &*self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let mut bounds = Aabb::empty();
+        for vertex in &self.vertices {
+            bounds.grow(&vertex.position);
+        }
+        bounds
+    }
+}
+
+// Malla de triángulos con su propio BVH interno
+pub struct Mesh {
+    bvh: Bvh<Triangle>,
+}
+
+impl Mesh {
+    pub fn new(triangles: Vec<Triangle>) -> Self {
+        Mesh {
+            bvh: Bvh::new(triangles),
+        }
+    }
+
+    pub fn triangles(&self) -> &[Triangle] {
+        self.bvh.objects()
+    }
+}
+
+impl RayIntersect for Mesh {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        self.bvh.ray_intersect(ray)
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.bvh.occluder(ray)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bvh.bounding_box()
+    }
+}
@@ -0,0 +1,98 @@
+use crate::aabb::Aabb;
+use crate::transform::Frame;
+use nalgebra_glm::{self as glm, Mat4, Quat, Vec3};
+
+// Muestras a lo largo del obturador con las que se acota el recorrido del objeto
+const BOUND_STEPS: usize = 16;
+
+// Posición, giro y escala de un objeto respecto a su forma en reposo
+#[derive(Debug, Clone, Copy)]
+pub struct Pose {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Pose {
+    pub fn identity() -> Self {
+        Pose {
+            translation: Vec3::zeros(),
+            rotation: glm::quat_identity(),
+            scale: Vec3::new(1.0, 1.0, 1.0),
+        }
+    }
+
+    pub fn translate(mut self, offset: Vec3) -> Self {
+        self.translation += offset;
+        self
+    }
+
+    pub fn rotate(mut self, angle: f32, axis: Vec3) -> Self {
+        self.rotation = glm::quat_angle_axis(angle, &axis.normalize()) * self.rotation;
+        self
+    }
+
+    #[allow(dead_code)]
+    pub fn scale(mut self, factors: Vec3) -> Self {
+        self.scale = self.scale.component_mul(&factors);
+        self
+    }
+
+    // El giro y la escala se aplican alrededor de `pivot` (el centro del objeto en reposo)
+    pub fn matrix(&self, pivot: &Vec3) -> Mat4 {
+        glm::translation(&(pivot + self.translation))
+            * glm::quat_to_mat4(&self.rotation)
+            * glm::scaling(&self.scale)
+            * glm::translation(&-pivot)
+    }
+}
+
+// Movimiento de un objeto durante el obturador: `start` en la apertura (time = 0) y `end` al cierre (time = 1)
+#[derive(Debug, Clone, Copy)]
+pub struct Motion {
+    pub start: Pose,
+    pub end: Pose,
+}
+
+impl Motion {
+    pub fn new(start: Pose, end: Pose) -> Self {
+        Motion { start, end }
+    }
+
+    // Desplazamiento simple, p. ej. un pistón o un bloque que cae
+    #[allow(dead_code)]
+    pub fn linear(offset: Vec3) -> Self {
+        Motion::new(Pose::identity(), Pose::identity().translate(offset))
+    }
+
+    pub fn pose_at(&self, time: f32) -> Pose {
+        let time = time.clamp(0.0, 1.0);
+        Pose {
+            translation: glm::lerp(&self.start.translation, &self.end.translation, time),
+            rotation: glm::quat_slerp(&self.start.rotation, &self.end.rotation, time),
+            scale: glm::lerp(&self.start.scale, &self.end.scale, time),
+        }
+    }
+
+    pub fn frame_at(&self, time: f32, pivot: &Vec3) -> Frame {
+        Frame::new(self.pose_at(time).matrix(pivot))
+    }
+
+    // Caja que cubre todo el recorrido de `rest` durante el obturador
+    pub fn bounds(&self, rest: &Aabb, pivot: &Vec3) -> Aabb {
+        let mut bounds = Aabb::empty();
+        for step in 0..=BOUND_STEPS {
+            let time = step as f32 / BOUND_STEPS as f32;
+            bounds = bounds.union(&self.frame_at(time, pivot).transform_box(rest));
+        }
+
+        // Entre dos muestras las esquinas giran sobre un arco que se sale de la recta que las une;
+        // se agranda la caja con la flecha de ese arco para no recortar el objeto
+        let turn = glm::quat_angle(&(self.end.rotation * glm::quat_conjugate(&self.start.rotation)));
+        let max_scale = self.start.scale.abs().max().max(self.end.scale.abs().max());
+        let radius = ((rest.max - rest.min).magnitude() * 0.5 + rest.centroid().metric_distance(pivot)) * max_scale;
+        let sagitta = radius * (1.0 - (turn / (2.0 * BOUND_STEPS as f32)).cos());
+        let padding = Vec3::new(sagitta, sagitta, sagitta);
+        Aabb::new(bounds.min - padding, bounds.max + padding)
+    }
+}
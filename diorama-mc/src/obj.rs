@@ -0,0 +1,467 @@
+use crate::color::Color;
+use crate::material::Material;
+use crate::mesh::{Mesh, Triangle, Vertex};
+use crate::pbr::Pbr;
+use crate::texture::{Texture, Wrap};
+use nalgebra_glm::{Vec2, Vec3};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+
+// Carga un archivo Wavefront OBJ (y sus bibliotecas MTL) como una malla de triángulos
+pub fn load_obj(file_path: &str) -> Mesh {
+    let source = fs::read_to_string(file_path).expect("Failed to open OBJ file");
+    let base_dir = Path::new(file_path).parent().unwrap_or(Path::new(""));
+
+    let mut positions: Vec<Vec3> = Vec::new();
+    let mut normals: Vec<Vec3> = Vec::new();
+    let mut uvs: Vec<Vec2> = Vec::new();
+
+    let mut materials: HashMap<String, Arc<Material>> = HashMap::new();
+    let default_material = Arc::new(default_material());
+    let mut current_material = default_material.clone();
+
+    let mut triangles: Vec<Triangle> = Vec::new();
+
+    for (line_number, line) in source.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(k) if !k.starts_with('#') => k,
+            _ => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => positions.push(parse_vec3(&args, line_number)),
+            "vn" => normals.push(parse_vec3(&args, line_number)),
+            "vt" => {
+                let values = parse_floats(&args, line_number);
+                uvs.push(Vec2::new(
+                    values.first().copied().unwrap_or(0.0),
+                    values.get(1).copied().unwrap_or(0.0),
+                ));
+            }
+            "f" => {
+                let face: Vec<Vertex> = args
+                    .iter()
+                    .map(|corner| parse_corner(corner, &positions, &normals, &uvs, line_number))
+                    .collect();
+
+                // Triangular el polígono en abanico
+                for i in 1..face.len().saturating_sub(1) {
+                    let mut vertices = [face[0], face[i], face[i + 1]];
+
+                    // Sin normales en el archivo: usar la normal geométrica de la cara
+                    if vertices.iter().any(|v| v.normal == Vec3::zeros()) {
+                        let a = vertices[0].position;
+                        let normal = (vertices[1].position - a).cross(&(vertices[2].position - a));
+                        if normal.magnitude() > 0.0 {
+                            for vertex in vertices.iter_mut() {
+                                vertex.normal = normal.normalize();
+                            }
+                        }
+                    }
+
+                    triangles.push(Triangle::new(vertices, current_material.clone()));
+                }
+            }
+            "mtllib" => {
+                // Varias bibliotecas van separadas por espacios, pero un nombre también puede tenerlos
+                let whole = base_dir.join(statement_rest(line));
+                if whole.is_file() {
+                    materials.extend(load_mtl(&whole));
+                } else {
+                    for library in args {
+                        materials.extend(load_mtl(&base_dir.join(library)));
+                    }
+                }
+            }
+            "usemtl" => {
+                // El nombre es todo el resto de la línea, igual que en `newmtl`
+                let name = statement_rest(line);
+                current_material = match materials.get(name) {
+                    Some(material) => material.clone(),
+                    None => {
+                        eprintln!(
+                            "Warning: unknown material '{}' on line {} of {}, using the default",
+                            name,
+                            line_number + 1,
+                            file_path
+                        );
+                        default_material.clone()
+                    }
+                };
+            }
+            _ => (), // Grupos, objetos y suavizado no afectan el trazado
+        }
+    }
+
+    Mesh::new(triangles)
+}
+
+// Convierte cada entrada `newmtl` de un archivo MTL en un Material. Lo que no se soporta
+// (instrucciones desconocidas, colores espectrales o XYZ, ...) se ignora con un aviso
+pub fn load_mtl(file_path: &Path) -> HashMap<String, Arc<Material>> {
+    let source = fs::read_to_string(file_path).expect("Failed to open MTL file");
+    let base_dir = file_path.parent().unwrap_or(Path::new(""));
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, MtlEntry)> = None;
+    let mut unsupported = BTreeSet::new();
+
+    for (line_number, line) in source.lines().enumerate() {
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(k) if !k.starts_with('#') => k,
+            _ => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            if let Some((name, entry)) = current.take() {
+                materials.insert(name, Arc::new(entry.into_material()));
+            }
+            current = Some((statement_rest(line).to_string(), MtlEntry::default()));
+            continue;
+        }
+
+        let entry = match current.as_mut() {
+            Some((_, entry)) => entry,
+            None => continue,
+        };
+
+        let parsed = match keyword {
+            "Kd" => mtl_color(&args).map(|rgb| entry.diffuse = rgb),
+            "Ks" => mtl_color(&args).map(|rgb| entry.specular = rgb),
+            "Ke" => mtl_color(&args).map(|rgb| entry.emission = rgb),
+            "Ns" => mtl_float(&args).map(|value| entry.shininess = value),
+            "Pm" => mtl_float(&args).map(|value| entry.metallic = Some(value)),
+            "Pr" => mtl_float(&args).map(|value| entry.roughness = Some(value)),
+            "Ni" => mtl_float(&args).map(|value| entry.refractive_index = value),
+            "d" => mtl_float(&args).map(|value| entry.dissolve = value),
+            "Tr" => mtl_float(&args).map(|value| entry.dissolve = 1.0 - value),
+            "illum" => mtl_float(&args).map(|value| entry.illum = value as u32),
+            "map_Kd" | "map_Ke" => {
+                let (texture_file, clamp) = parse_map(statement_rest(line));
+                if texture_file.is_empty() {
+                    None
+                } else {
+                    let path = base_dir.join(texture_file);
+                    let texture = Some(Arc::new(Texture::new(&path.to_string_lossy())));
+                    if keyword == "map_Kd" {
+                        entry.texture = texture;
+                    } else {
+                        entry.emission_map = texture;
+                    }
+                    if clamp {
+                        entry.wrap = Wrap::Clamp;
+                    }
+                    Some(())
+                }
+            }
+            _ => {
+                unsupported.insert(keyword.to_string());
+                Some(())
+            }
+        };
+        if parsed.is_none() {
+            eprintln!(
+                "Warning: ignoring unsupported '{}' on line {} of {}",
+                line.trim(),
+                line_number + 1,
+                file_path.display()
+            );
+        }
+    }
+
+    if let Some((name, entry)) = current.take() {
+        materials.insert(name, Arc::new(entry.into_material()));
+    }
+    if !unsupported.is_empty() {
+        let keywords: Vec<String> = unsupported.into_iter().collect();
+        eprintln!(
+            "Warning: ignoring unsupported MTL statements in {}: {}",
+            file_path.display(),
+            keywords.join(", ")
+        );
+    }
+    materials
+}
+
+// Lo que sigue a la palabra clave, tal cual (los nombres de archivo pueden tener espacios)
+fn statement_rest(line: &str) -> &str {
+    let line = line.trim();
+    line.find(char::is_whitespace).map_or("", |end| line[end..].trim())
+}
+
+// Nombre del archivo de un `map_*` y si pide `-clamp on`. Las opciones (-s, -o, -bm, ...)
+// van antes del nombre
+fn parse_map(statement: &str) -> (&str, bool) {
+    let mut rest = statement;
+    let mut clamp = false;
+    while rest.starts_with('-') {
+        let (option, mut after) = next_token(rest);
+        // -o, -s y -t llevan de 1 a 3 números, -mm 2 y las demás 1 valor
+        let count = match option {
+            "-o" | "-s" | "-t" => 3,
+            "-mm" => 2,
+            _ => 1,
+        };
+        for i in 0..count {
+            let (value, next) = next_token(after);
+            if i > 0 && value.parse::<f32>().is_err() {
+                break;
+            }
+            if option == "-clamp" {
+                clamp = value == "on";
+            }
+            after = next;
+        }
+        rest = after;
+    }
+    (rest, clamp)
+}
+
+fn next_token(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], text[end..].trim_start()),
+        None => (text, ""),
+    }
+}
+
+// Un número de MTL; None si falta o no se entiende
+fn mtl_float(args: &[&str]) -> Option<f32> {
+    args.first()?.parse().ok()
+}
+
+// Color RGB de MTL; con un solo valor los tres canales son iguales. None para las formas
+// `spectral` y `xyz`, que no se soportan
+fn mtl_color(args: &[&str]) -> Option<Vec3> {
+    let values = args.iter().map(|a| a.parse::<f32>().ok()).collect::<Option<Vec<f32>>>()?;
+    match values[..] {
+        [gray] => Some(Vec3::new(gray, gray, gray)),
+        [r, g, b, ..] => Some(Vec3::new(r, g, b)),
+        _ => None,
+    }
+}
+
+struct MtlEntry {
+    diffuse: Vec3,
+    specular: Vec3,
+    emission: Vec3,
+    shininess: f32,
+    refractive_index: f32,
+    dissolve: f32,
+    illum: u32,
+    metallic: Option<f32>,  // Extensión PBR de MTL (Pm)
+    roughness: Option<f32>, // Extensión PBR de MTL (Pr)
+    texture: Option<Arc<Texture>>,
+    emission_map: Option<Arc<Texture>>,
+    wrap: Wrap, // Las UV de los modelos suelen salirse de 0..1 para repetir la textura
+}
+
+impl Default for MtlEntry {
+    fn default() -> Self {
+        MtlEntry {
+            diffuse: Vec3::new(0.8, 0.8, 0.8),
+            specular: Vec3::zeros(),
+            emission: Vec3::zeros(),
+            shininess: 10.0,
+            refractive_index: 1.0,
+            dissolve: 1.0,
+            illum: 2,
+            metallic: None,
+            roughness: None,
+            texture: None,
+            emission_map: None,
+            wrap: Wrap::Repeat,
+        }
+    }
+}
+
+impl MtlEntry {
+    fn into_material(self) -> Material {
+        let transparency = (1.0 - self.dissolve).clamp(0.0, 1.0);
+        let specular = (self.specular.x + self.specular.y + self.specular.z) / 3.0;
+        // illum 3 a 7 activan el trazado de reflejos
+        let reflectivity = if (3..=7).contains(&self.illum) { specular } else { 0.0 };
+        let albedo = [1.0 - transparency, specular, reflectivity, transparency];
+
+        // Ke puede pasar de 1 (emisores HDR): el color se normaliza y el resto va a la intensidad
+        let emission_strength = self.emission.max().max(1.0);
+        let mut material = Material::new(
+            to_color(&self.diffuse),
+            self.shininess,
+            albedo,
+            self.refractive_index,
+            to_color(&(self.emission / emission_strength)),
+        )
+        .with_emission_strength(emission_strength)
+        .with_wrap(self.wrap);
+        if let Some(emission_map) = self.emission_map {
+            material = material.with_emission_map(emission_map);
+        }
+        if let Some(texture) = self.texture {
+            material.has_texture = true;
+            material.texture = Some(texture.into());
+        }
+        if self.metallic.is_some() || self.roughness.is_some() {
+            let phong = Pbr::from_phong(self.shininess);
+            material.pbr = Some(Pbr::new(
+                self.metallic.unwrap_or(phong.metallic),
+                self.roughness.unwrap_or(phong.roughness),
+            ));
+        }
+        material
+    }
+}
+
+fn default_material() -> Material {
+    MtlEntry::default().into_material()
+}
+
+fn to_color(rgb: &Vec3) -> Color {
+    Color::new(
+        (rgb.x.clamp(0.0, 1.0) * 255.0) as u8,
+        (rgb.y.clamp(0.0, 1.0) * 255.0) as u8,
+        (rgb.z.clamp(0.0, 1.0) * 255.0) as u8,
+    )
+}
+
+fn parse_floats(args: &[&str], line_number: usize) -> Vec<f32> {
+    let values: Vec<f32> = args
+        .iter()
+        .map(|a| {
+            a.parse::<f32>()
+                .unwrap_or_else(|_| panic!("Invalid number '{}' on line {}", a, line_number + 1))
+        })
+        .collect();
+    assert!(!values.is_empty(), "Missing values on line {}", line_number + 1);
+    values
+}
+
+fn parse_vec3(args: &[&str], line_number: usize) -> Vec3 {
+    let values = parse_floats(args, line_number);
+    assert!(values.len() >= 3, "Expected 3 values on line {}", line_number + 1);
+    Vec3::new(values[0], values[1], values[2])
+}
+
+// Índices de OBJ: empiezan en 1 y los negativos cuentan desde el final
+fn resolve_index(token: &str, len: usize, line_number: usize) -> Option<usize> {
+    if token.is_empty() {
+        return None;
+    }
+    let index: i64 = token
+        .parse()
+        .unwrap_or_else(|_| panic!("Invalid index '{}' on line {}", token, line_number + 1));
+    let resolved = if index < 0 { len as i64 + index } else { index - 1 };
+    assert!(
+        resolved >= 0 && (resolved as usize) < len,
+        "Index {} out of range on line {}",
+        index,
+        line_number + 1
+    );
+    Some(resolved as usize)
+}
+
+// Una esquina de cara con la forma v, v/vt, v//vn o v/vt/vn
+fn parse_corner(corner: &str, positions: &[Vec3], normals: &[Vec3], uvs: &[Vec2], line_number: usize) -> Vertex {
+    let mut parts = corner.split('/');
+    let position = parts
+        .next()
+        .and_then(|p| resolve_index(p, positions.len(), line_number))
+        .map(|i| positions[i])
+        .unwrap_or_else(|| panic!("Face without vertex on line {}", line_number + 1));
+    let uv = parts
+        .next()
+        .and_then(|t| resolve_index(t, uvs.len(), line_number))
+        .map(|i| uvs[i])
+        .unwrap_or(Vec2::zeros());
+    let normal = parts
+        .next()
+        .and_then(|n| resolve_index(n, normals.len(), line_number))
+        .map(|i| normals[i])
+        .unwrap_or(Vec3::zeros());
+
+    Vertex { position, normal, uv }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Carpeta temporal propia de cada prueba, con los archivos dados
+    fn write_files(test: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("diorama-obj-{}-{}", test, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for (name, contents) in files {
+            fs::write(dir.join(name), contents).unwrap();
+        }
+        dir
+    }
+
+    fn rgb(color: Color) -> (u8, u8, u8) {
+        (color.r(), color.g(), color.b())
+    }
+
+    #[test]
+    fn names_with_spaces_resolve_materials() {
+        let dir = write_files(
+            "spaces",
+            &[
+                ("my lib.mtl", "newmtl my  red mat\nKd 1 0 0\nnewmtl other\nKd 0 0 1\n"),
+                (
+                    "model.obj",
+                    "mtllib my lib.mtl\nv 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl my  red mat\nf 1 2 3\nusemtl other\nf 1 2 3\n",
+                ),
+            ],
+        );
+        let mesh = load_obj(&dir.join("model.obj").to_string_lossy());
+        let colors: Vec<_> = mesh.triangles().iter().map(|t| rgb(t.material.diffuse)).collect();
+        assert_eq!(colors, vec![(255, 0, 0), (0, 0, 255)]);
+    }
+
+    #[test]
+    fn unknown_material_falls_back_to_default() {
+        let dir = write_files("unknown", &[("model.obj", "v 0 0 0\nv 1 0 0\nv 0 1 0\nusemtl missing\nf 1 2 3\n")]);
+        let mesh = load_obj(&dir.join("model.obj").to_string_lossy());
+        assert_eq!(rgb(mesh.triangles()[0].material.diffuse), rgb(default_material().diffuse));
+    }
+
+    #[test]
+    fn unsupported_statements_are_skipped() {
+        let dir = write_files(
+            "unsupported",
+            &[(
+                "materials.mtl",
+                "newmtl glass\nKa 0.1 0.1 0.1\nKd spectral glass.spd\nKs 0.5\nd -halo 0.5\nTf 1 1 1\nNi 1.5\nKd 0 1 0\n",
+            )],
+        );
+        let materials = load_mtl(&dir.join("materials.mtl"));
+        let glass = &materials["glass"];
+        assert_eq!(rgb(glass.diffuse), (0, 255, 0));
+        assert_eq!(glass.refractive_index, 1.5);
+        assert_eq!(glass.albedo[1], 0.5);
+    }
+
+    #[test]
+    fn texture_options_and_wrap() {
+        let dir = write_files(
+            "maps",
+            &[(
+                "materials.mtl",
+                "newmtl tiled\nmap_Kd -s 2 2 1 -bm 1 tex dir/stone tex.png\nnewmtl clamped\nmap_Kd -clamp on tex dir/stone tex.png\n",
+            )],
+        );
+        fs::create_dir_all(dir.join("tex dir")).unwrap();
+        image::RgbImage::new(2, 2).save(dir.join("tex dir/stone tex.png")).unwrap();
+        let materials = load_mtl(&dir.join("materials.mtl"));
+        assert!(materials["tiled"].texture.is_some());
+        assert_eq!(materials["tiled"].wrap, Wrap::Repeat);
+        assert_eq!(materials["clamped"].wrap, Wrap::Clamp);
+        assert_eq!(parse_map("-o 0.5 -mm 0 1 name.png"), ("name.png", false));
+        assert_eq!(parse_map("-clamp on -o 1 2 my file.png"), ("my file.png", true));
+    }
+}
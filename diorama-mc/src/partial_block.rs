@@ -0,0 +1,219 @@
+use crate::aabb::Aabb;
+use crate::cube::{box_face_hit, box_hit_distance, box_normal};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Un bloque mide 16 píxeles por lado, como en los modelos de Minecraft
+const BLOCK_PIXELS: f32 = 16.0;
+
+// Como en Minecraft: norte = -Z, sur = +Z, este = +X, oeste = -X
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Facing {
+    North,
+    South,
+    East,
+    West,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Half {
+    Bottom,
+    Top,
+}
+
+// Lados hacia los que una cerca o un muro se une con sus vecinos
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Connections {
+    pub north: bool,
+    pub south: bool,
+    pub east: bool,
+    pub west: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BlockShape {
+    Slab(Half),
+    Stairs { facing: Facing, half: Half }, // `facing` es el lado de la parte alta
+    Fence(Connections),
+    Wall(Connections),
+}
+
+impl BlockShape {
+    // Cajas que forman el bloque, en píxeles (0 a 16) dentro de la celda
+    pub fn elements(&self) -> Vec<([f32; 3], [f32; 3])> {
+        match *self {
+            BlockShape::Slab(Half::Bottom) => vec![([0.0, 0.0, 0.0], [16.0, 8.0, 16.0])],
+            BlockShape::Slab(Half::Top) => vec![([0.0, 8.0, 0.0], [16.0, 16.0, 16.0])],
+            BlockShape::Stairs { facing, half } => {
+                let (base, step) = match half {
+                    Half::Bottom => ((0.0, 8.0), (8.0, 16.0)),
+                    Half::Top => ((8.0, 16.0), (0.0, 8.0)),
+                };
+                let (x, z) = match facing {
+                    Facing::North => ((0.0, 16.0), (0.0, 8.0)),
+                    Facing::South => ((0.0, 16.0), (8.0, 16.0)),
+                    Facing::East => ((8.0, 16.0), (0.0, 16.0)),
+                    Facing::West => ((0.0, 8.0), (0.0, 16.0)),
+                };
+                vec![
+                    ([0.0, base.0, 0.0], [16.0, base.1, 16.0]),
+                    ([x.0, step.0, z.0], [x.1, step.1, z.1]),
+                ]
+            }
+            BlockShape::Fence(connections) => {
+                let mut elements = vec![([6.0, 0.0, 6.0], [10.0, 16.0, 10.0])];
+                // Dos travesaños por cada lado conectado
+                for (y0, y1) in [(6.0, 9.0), (12.0, 15.0)] {
+                    if connections.north {
+                        elements.push(([7.0, y0, 0.0], [9.0, y1, 6.0]));
+                    }
+                    if connections.south {
+                        elements.push(([7.0, y0, 10.0], [9.0, y1, 16.0]));
+                    }
+                    if connections.west {
+                        elements.push(([0.0, y0, 7.0], [6.0, y1, 9.0]));
+                    }
+                    if connections.east {
+                        elements.push(([10.0, y0, 7.0], [16.0, y1, 9.0]));
+                    }
+                }
+                elements
+            }
+            BlockShape::Wall(connections) => {
+                let mut elements = vec![([4.0, 0.0, 4.0], [12.0, 16.0, 12.0])];
+                if connections.north {
+                    elements.push(([5.0, 0.0, 0.0], [11.0, 14.0, 4.0]));
+                }
+                if connections.south {
+                    elements.push(([5.0, 0.0, 12.0], [11.0, 14.0, 16.0]));
+                }
+                if connections.west {
+                    elements.push(([0.0, 0.0, 5.0], [4.0, 14.0, 11.0]));
+                }
+                if connections.east {
+                    elements.push(([12.0, 0.0, 5.0], [16.0, 14.0, 11.0]));
+                }
+                elements
+            }
+        }
+    }
+}
+
+// Bloque que no ocupa la celda completa, formado por varias cajas con el mismo material
+pub struct PartialBlock {
+    pub position: Vec3, // Esquina mínima de la celda
+    #[allow(dead_code)] // Las cajas ya salen de la forma; se guarda para quien arme la escena
+    pub shape: BlockShape,
+    pub material: Material,
+    elements: Vec<Aabb>,
+}
+
+impl PartialBlock {
+    pub fn new(position: Vec3, shape: BlockShape, material: Material) -> Self {
+        let elements = shape
+            .elements()
+            .iter()
+            .map(|(from, to)| {
+                Aabb::new(
+                    position + Vec3::new(from[0], from[1], from[2]) / BLOCK_PIXELS,
+                    position + Vec3::new(to[0], to[1], to[2]) / BLOCK_PIXELS,
+                )
+            })
+            .collect();
+        PartialBlock {
+            position,
+            shape,
+            material,
+            elements,
+        }
+    }
+
+    fn closest_element(&self, ray: &Ray) -> Option<(f32, &Aabb)> {
+        let mut closest: Option<(f32, &Aabb)> = None;
+        for element in &self.elements {
+            if let Some(t) = box_hit_distance(&element.min, &element.max, ray) {
+                if closest.is_none_or(|(best, _)| t < best) {
+                    closest = Some((t, element));
+                }
+            }
+        }
+        closest
+    }
+}
+
+impl RayIntersect for PartialBlock {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.closest_element(ray) {
+            Some((t, element)) => {
+                let point = ray.at(t);
+                let normal = box_normal(&element.min, &element.max, &point);
+
+                // La UV se toma respecto a la celda completa y no a la caja: cada cara muestra
+                // la misma porción de textura que ocuparía en un bloque entero, como en Minecraft
+                let cell_max = self.position + Vec3::new(1.0, 1.0, 1.0);
+                let mut intersect = box_face_hit(&self.position, &cell_max, point, normal, t, &self.material);
+                intersect.set_face_normal(&ray.direction, &normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.elements
+            .iter()
+            .any(|element| box_hit_distance(&element.min, &element.max, ray).is_some())
+            .then_some(&self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.elements
+            .iter()
+            .fold(Aabb::empty(), |bounds, element| bounds.union(element))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn block(shape: BlockShape) -> PartialBlock {
+        let material = Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black());
+        PartialBlock::new(Vec3::new(0.0, 0.0, 0.0), shape, material)
+    }
+
+    fn down_at(x: f32, z: f32) -> Ray {
+        Ray::new(Vec3::new(x, 5.0, z), Vec3::new(0.0, -1.0, 0.0))
+    }
+
+    #[test]
+    fn bottom_slab_fills_the_lower_half() {
+        let slab = block(BlockShape::Slab(Half::Bottom));
+        let hit = slab.ray_intersect(&down_at(0.5, 0.5));
+        assert!(hit.is_intersecting && hit.front_face);
+        assert!((hit.distance - 4.5).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec3::new(0.0, 1.0, 0.0));
+
+        // Por la mitad de arriba se pasa de largo
+        let across = Ray::new(Vec3::new(-1.0, 0.75, 0.5), Vec3::new(1.0, 0.0, 0.0));
+        assert!(!slab.ray_intersect(&across).is_intersecting);
+        assert!(!slab.occluded(&across));
+    }
+
+    #[test]
+    fn stairs_step_is_on_the_facing_side() {
+        let stairs = block(BlockShape::Stairs { facing: Facing::North, half: Half::Bottom });
+        // El escalón alto está al norte (-Z); al sur solo queda la base
+        assert!((stairs.ray_intersect(&down_at(0.5, 0.25)).distance - 4.0).abs() < 1e-5);
+        assert!((stairs.ray_intersect(&down_at(0.5, 0.75)).distance - 4.5).abs() < 1e-5);
+
+        // Desde el sur, a media altura, se choca con la contrahuella del escalón
+        let towards_north = Ray::new(Vec3::new(0.5, 0.75, 3.0), Vec3::new(0.0, 0.0, -1.0));
+        let hit = stairs.ray_intersect(&towards_north);
+        assert!((hit.point.z - 0.5).abs() < 1e-5);
+        assert_eq!(hit.normal, Vec3::new(0.0, 0.0, 1.0));
+    }
+}
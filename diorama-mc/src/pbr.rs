@@ -0,0 +1,99 @@
+use crate::color::Color;
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+// Por debajo de esta rugosidad el lóbulo de GGX es tan estrecho que las luces puntuales desaparecen
+const MIN_ROUGHNESS: f32 = 0.03;
+
+// Parámetros metallic/roughness. El color base sale de Material::diffuse (o su textura),
+// el IOR de Material::refractive_index y la emisión de Material::emission
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pbr {
+    pub metallic: f32,  // 0 = dieléctrico, 1 = metal (el reflejo toma el color base)
+    pub roughness: f32, // 0 = espejo, 1 = completamente mate
+}
+
+impl Pbr {
+    pub fn new(metallic: f32, roughness: f32) -> Self {
+        Pbr {
+            metallic: metallic.clamp(0.0, 1.0),
+            roughness: roughness.clamp(0.0, 1.0),
+        }
+    }
+
+    // Equivalencia entre el exponente de Blinn-Phong y GGX: alpha² = 2 / (n + 2), con alpha = roughness²
+    pub fn from_phong(specular_exponent: f32) -> Self {
+        let alpha = (2.0 / (specular_exponent.max(0.0) + 2.0)).sqrt();
+        Pbr::new(0.0, alpha.sqrt())
+    }
+
+    fn alpha(&self) -> f32 {
+        let roughness = self.roughness.max(MIN_ROUGHNESS);
+        roughness * roughness
+    }
+}
+
+pub fn color_to_vec(color: Color) -> Vec3 {
+    Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32) / 255.0
+}
+
+pub fn vec_to_color(rgb: &Vec3) -> Color {
+    Color::new(
+        (rgb.x.clamp(0.0, 1.0) * 255.0) as u8,
+        (rgb.y.clamp(0.0, 1.0) * 255.0) as u8,
+        (rgb.z.clamp(0.0, 1.0) * 255.0) as u8,
+    )
+}
+
+// Reflectancia a incidencia normal: la del IOR para dieléctricos, el color base para metales
+pub fn base_reflectance(base_color: &Vec3, refractive_index: f32, metallic: f32) -> Vec3 {
+    let r0 = ((1.0 - refractive_index) / (1.0 + refractive_index)).powi(2);
+    Vec3::new(r0, r0, r0) * (1.0 - metallic) + base_color * metallic
+}
+
+pub fn fresnel_schlick(cos_theta: f32, f0: &Vec3) -> Vec3 {
+    let weight = (1.0 - cos_theta.clamp(0.0, 1.0)).powi(5);
+    f0 + (Vec3::new(1.0, 1.0, 1.0) - f0) * weight
+}
+
+fn distribution_ggx(n_dot_h: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    a2 / (PI * d * d)
+}
+
+fn smith_g1(n_dot_x: f32, alpha: f32) -> f32 {
+    let a2 = alpha * alpha;
+    2.0 * n_dot_x / (n_dot_x + (a2 + (1.0 - a2) * n_dot_x * n_dot_x).sqrt())
+}
+
+// BRDF (sin el coseno) separado en su parte difusa (Lambert) y especular (Cook–Torrance con GGX)
+pub fn ggx_brdf(
+    normal: &Vec3,
+    view_dir: &Vec3,
+    light_dir: &Vec3,
+    base_color: &Vec3,
+    refractive_index: f32,
+    pbr: &Pbr,
+) -> (Vec3, Vec3) {
+    let n_dot_l = normal.dot(light_dir);
+    let n_dot_v = normal.dot(view_dir);
+    if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+        return (Vec3::zeros(), Vec3::zeros());
+    }
+
+    let half = (view_dir + light_dir).normalize();
+    let n_dot_h = normal.dot(&half).max(0.0);
+    let v_dot_h = view_dir.dot(&half).max(0.0);
+
+    let alpha = pbr.alpha();
+    let f0 = base_reflectance(base_color, refractive_index, pbr.metallic);
+    let fresnel = fresnel_schlick(v_dot_h, &f0);
+    let geometry = smith_g1(n_dot_l, alpha) * smith_g1(n_dot_v, alpha);
+    let specular = fresnel * (distribution_ggx(n_dot_h, alpha) * geometry / (4.0 * n_dot_l * n_dot_v));
+
+    // Lo que no se refleja en la superficie se difunde, salvo en los metales
+    let diffuse = (Vec3::new(1.0, 1.0, 1.0) - fresnel).component_mul(base_color) * ((1.0 - pbr.metallic) / PI);
+
+    (diffuse, specular)
+}
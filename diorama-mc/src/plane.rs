@@ -0,0 +1,87 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+const EPSILON: f32 = 1e-6;
+
+// Plano infinito; la textura se repite cada `texture_scale` unidades
+pub struct Plane {
+    pub point: Vec3,
+    pub normal: Vec3,
+    pub texture_scale: f32,
+    pub material: Material,
+}
+
+impl Plane {
+    pub fn new(point: Vec3, normal: Vec3, texture_scale: f32, material: Material) -> Self {
+        Plane {
+            point,
+            normal: normal.normalize(),
+            texture_scale,
+            material,
+        }
+    }
+
+    pub fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal);
+        let local = point - self.point;
+        (
+            (local.dot(&tangent) / self.texture_scale).rem_euclid(1.0),
+            (local.dot(&bitangent) / self.texture_scale).rem_euclid(1.0),
+        )
+    }
+}
+
+// Dos ejes perpendiculares a `normal` para parametrizar superficies planas
+pub fn orthonormal_basis(normal: &Vec3) -> (Vec3, Vec3) {
+    let helper = if normal.y.abs() < 0.999 {
+        Vec3::new(0.0, 1.0, 0.0)
+    } else {
+        Vec3::new(0.0, 0.0, 1.0)
+    };
+    let tangent = helper.cross(normal).normalize();
+    let bitangent = normal.cross(&tangent);
+    (tangent, bitangent)
+}
+
+// Distancia a lo largo del rayo hasta el plano (point, normal), si cae dentro del rango del rayo
+pub fn ray_plane_distance(ray: &Ray, point: &Vec3, normal: &Vec3) -> Option<f32> {
+    let denom = normal.dot(&ray.direction);
+    if denom.abs() < EPSILON {
+        return None;
+    }
+    let t = (point - ray.origin).dot(normal) / denom;
+    if t > EPSILON && ray.in_range(t) {
+        Some(t)
+    } else {
+        None
+    }
+}
+
+impl RayIntersect for Plane {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match ray_plane_distance(ray, &self.point, &self.normal) {
+            Some(t) => {
+                let point = ray.at(t);
+                let (u, v) = self.get_uv(&point);
+                let (tangent, bitangent) = orthonormal_basis(&self.normal);
+                let mut intersect = Intersect::new(point, self.normal, t, &self.material, u, v)
+                    .with_tangents(tangent, bitangent)
+                    .with_uv_density(1.0 / self.texture_scale);
+                intersect.set_face_normal(&ray.direction, &self.normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        ray_plane_distance(ray, &self.point, &self.normal).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        Aabb::infinite()
+    }
+}
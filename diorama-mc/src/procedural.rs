@@ -0,0 +1,171 @@
+use crate::color::Color;
+use crate::pbr::{color_to_vec, vec_to_color};
+use nalgebra_glm::{Vec2, Vec3};
+use std::f32::consts::PI;
+
+// Texturas calculadas a partir de la UV, sin archivo de imagen. Cada patrón da un valor de 0 a 1
+// que mezcla sus dos colores
+#[derive(Debug, Clone)]
+pub enum Procedural {
+    // Ruido fBm: `octaves` capas de Perlin, cada una al doble de frecuencia y la mitad de amplitud
+    Noise { scale: f32, octaves: u32, seed: u32, low: Color, high: Color },
+    // Tablero con `cells` casillas por lado
+    Checker { cells: f32, even: Color, odd: Color },
+    // Bandas diagonales deformadas por el ruido; las vetas quedan donde cambian de signo
+    Marble { scale: f32, turbulence: f32, seed: u32, base: Color, vein: Color },
+    // Anillos alrededor del centro de la UV, deformados por el ruido
+    Wood { rings: f32, turbulence: f32, seed: u32, light: Color, dark: Color },
+    // De `from` a `to` en la dirección `angle` (radianes; 0 = a lo largo de u, π/2 = de abajo hacia arriba)
+    Gradient { angle: f32, from: Color, to: Color },
+}
+
+impl Procedural {
+    pub fn noise(scale: f32, octaves: u32, low: Color, high: Color) -> Procedural {
+        Procedural::Noise { scale, octaves: octaves.max(1), seed: 0, low, high }
+    }
+
+    pub fn checker(cells: f32, even: Color, odd: Color) -> Procedural {
+        Procedural::Checker { cells, even, odd }
+    }
+
+    pub fn marble(scale: f32, turbulence: f32, base: Color, vein: Color) -> Procedural {
+        Procedural::Marble { scale, turbulence, seed: 0, base, vein }
+    }
+
+    pub fn wood(rings: f32, turbulence: f32, light: Color, dark: Color) -> Procedural {
+        Procedural::Wood { rings, turbulence, seed: 0, light, dark }
+    }
+
+    pub fn gradient(angle: f32, from: Color, to: Color) -> Procedural {
+        Procedural::Gradient { angle, from, to }
+    }
+
+    // Otra variante del mismo patrón (no cambia el tablero ni el degradado)
+    pub fn with_seed(mut self, new_seed: u32) -> Procedural {
+        match &mut self {
+            Procedural::Noise { seed, .. } | Procedural::Marble { seed, .. } | Procedural::Wood { seed, .. } => {
+                *seed = new_seed
+            }
+            Procedural::Checker { .. } | Procedural::Gradient { .. } => (),
+        }
+        self
+    }
+
+    // Color en la UV. `footprint` es el ancho del píxel en UV: suaviza el tablero y descarta las
+    // octavas de ruido más finas que un píxel, para que de lejos no aparezca ruido de aliasing
+    pub fn sample(&self, u: f32, v: f32, footprint: f32) -> Color {
+        let (a, b) = self.colors();
+        let t = self.value(u, v, footprint).clamp(0.0, 1.0);
+        let a = color_to_vec(a);
+        vec_to_color(&(a + (color_to_vec(b) - a) * t))
+    }
+
+    // Valor del patrón de 0 a 1, antes de mezclar los colores
+    pub fn value(&self, u: f32, v: f32, footprint: f32) -> f32 {
+        match *self {
+            Procedural::Noise { scale, octaves, seed, .. } => {
+                let octaves = visible_octaves(octaves, scale * footprint);
+                0.5 + 0.5 * fbm(u * scale, v * scale, octaves, seed)
+            }
+            Procedural::Checker { cells, .. } => {
+                // Tablero filtrado con una caja del tamaño del píxel (integral analítica)
+                let p = Vec2::new(u, v) * cells;
+                let width = footprint * cells + 1e-3;
+                let edge = |x: f32| {
+                    let tri = |x: f32| ((x * 0.5).rem_euclid(1.0) - 0.5).abs();
+                    2.0 * (tri(x - 0.5 * width) - tri(x + 0.5 * width)) / width
+                };
+                0.5 - 0.5 * edge(p.x) * edge(p.y)
+            }
+            Procedural::Marble { scale, turbulence, seed, .. } => {
+                let octaves = visible_octaves(5, 4.0 * footprint);
+                let bands = (u + v) * scale + turbulence * fbm(u * 4.0, v * 4.0, octaves, seed);
+                (1.0 - (bands * PI).sin().abs()).powi(6)
+            }
+            Procedural::Wood { rings, turbulence, seed, .. } => {
+                let octaves = visible_octaves(3, 2.0 * footprint);
+                let distance = Vec2::new(u - 0.5, v - 0.5).magnitude();
+                let ring = (distance * rings + turbulence * fbm(u * 2.0, v * 2.0, octaves, seed)).rem_euclid(1.0);
+                // Madera clara en casi todo el anillo y una franja oscura al final
+                let t = ((ring - 0.65) / 0.35).clamp(0.0, 1.0);
+                t * t * (3.0 - 2.0 * t)
+            }
+            Procedural::Gradient { angle, .. } => 0.5 + (u - 0.5) * angle.cos() + (v - 0.5) * angle.sin(),
+        }
+    }
+
+    fn colors(&self) -> (Color, Color) {
+        match *self {
+            Procedural::Noise { low, high, .. } => (low, high),
+            Procedural::Checker { even, odd, .. } => (even, odd),
+            Procedural::Marble { base, vein, .. } => (base, vein),
+            Procedural::Wood { light, dark, .. } => (light, dark),
+            Procedural::Gradient { from, to, .. } => (from, to),
+        }
+    }
+
+    // Color medio sobre toda la UV, muestreando una rejilla (p. ej. para las luces emisivas)
+    pub fn average_color(&self) -> Color {
+        const STEPS: usize = 16;
+        let mut sum = Vec3::zeros();
+        for i in 0..STEPS {
+            for j in 0..STEPS {
+                let u = (i as f32 + 0.5) / STEPS as f32;
+                let v = (j as f32 + 0.5) / STEPS as f32;
+                sum += color_to_vec(self.sample(u, v, 0.0));
+            }
+        }
+        vec_to_color(&(sum / (STEPS * STEPS) as f32))
+    }
+}
+
+// Octavas cuyo detalle sigue siendo más grande que el píxel (`footprint` en unidades de la primera octava)
+fn visible_octaves(octaves: u32, footprint: f32) -> u32 {
+    if footprint <= 0.0 {
+        return octaves;
+    }
+    let finest = (1.0 / footprint).log2().floor() as i32 + 1;
+    (finest.max(1) as u32).min(octaves)
+}
+
+// Suma de octavas de Perlin normalizada, aproximadamente de -1 a 1
+pub fn fbm(x: f32, y: f32, octaves: u32, seed: u32) -> f32 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+    for octave in 0..octaves {
+        sum += amplitude * perlin(x * frequency, y * frequency, seed.wrapping_add(octave));
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
+
+// Ruido de gradiente de Perlin en 2D, aproximadamente de -1 a 1. Los gradientes salen de un hash de
+// la celda, así que no hace falta tabla de permutaciones
+pub fn perlin(x: f32, y: f32, seed: u32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (fx, fy) = (x - x0, y - y0);
+    let (ix, iy) = (x0 as i32, y0 as i32);
+
+    let corner = |dx: i32, dy: i32| {
+        let angle = hash(ix + dx, iy + dy, seed) as f32 / u32::MAX as f32 * 2.0 * PI;
+        angle.cos() * (fx - dx as f32) + angle.sin() * (fy - dy as f32)
+    };
+    // Curva de Perlin mejorada (6t⁵ - 15t⁴ + 10t³): la derivada es continua entre celdas
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (sx, sy) = (fade(fx), fade(fy));
+
+    let bottom = corner(0, 0) + (corner(1, 0) - corner(0, 0)) * sx;
+    let top = corner(0, 1) + (corner(1, 1) - corner(0, 1)) * sx;
+    // Con gradientes unitarios el rango es ±√½; se escala para llegar a ±1
+    (bottom + (top - bottom) * sy) * std::f32::consts::SQRT_2
+}
+
+fn hash(x: i32, y: i32, seed: u32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343)
+        ^ (y as u32).wrapping_mul(0xd816_3841)
+        ^ seed.wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 13;
+    h = h.wrapping_mul(0x5bd1_e995);
+    h ^ (h >> 15)
+}
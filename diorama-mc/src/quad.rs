@@ -0,0 +1,114 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::plane::ray_plane_distance;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Paralelogramo con esquina `corner` y lados `edge_u` y `edge_v`; la UV va de 0 a 1 sobre cada lado
+pub struct Quad {
+    pub corner: Vec3,
+    pub edge_u: Vec3,
+    pub edge_v: Vec3,
+    pub material: Material,
+}
+
+impl Quad {
+    pub fn new(corner: Vec3, edge_u: Vec3, edge_v: Vec3, material: Material) -> Self {
+        Quad {
+            corner,
+            edge_u,
+            edge_v,
+            material,
+        }
+    }
+
+    pub fn normal(&self) -> Vec3 {
+        self.edge_u.cross(&self.edge_v).normalize()
+    }
+
+    // Coordenadas del punto en la base (edge_u, edge_v); sirve aunque los lados no sean perpendiculares
+    pub fn get_uv(&self, point: &Vec3) -> (f32, f32) {
+        let n = self.edge_u.cross(&self.edge_v);
+        let w = n / n.dot(&n);
+        let local = point - self.corner;
+        (
+            w.dot(&local.cross(&self.edge_v)),
+            w.dot(&self.edge_u.cross(&local)),
+        )
+    }
+}
+
+impl RayIntersect for Quad {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let normal = self.normal();
+        if let Some(t) = ray_plane_distance(ray, &self.corner, &normal) {
+            let point = ray.at(t);
+            let (u, v) = self.get_uv(&point);
+            if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+                // u y v recorren cada lado completo; se usa la media geométrica de sus largos
+                let (length_u, length_v) = (self.edge_u.magnitude(), self.edge_v.magnitude());
+                let mut intersect = Intersect::new(point, normal, t, &self.material, u, v)
+                    .with_tangents(self.edge_u / length_u, self.edge_v / length_v)
+                    .with_uv_density(1.0 / (length_u * length_v).sqrt());
+                intersect.set_face_normal(&ray.direction, &normal);
+                return intersect;
+            }
+        }
+        Intersect::empty()
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let mut bounds = Aabb::empty();
+        for corner in [
+            self.corner,
+            self.corner + self.edge_u,
+            self.corner + self.edge_v,
+            self.corner + self.edge_u + self.edge_v,
+        ] {
+            bounds.grow(&corner);
+        }
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn quad() -> Quad {
+        // Lados de distinto largo y no perpendiculares, para que la UV no salga de la posición
+        Quad::new(
+            Vec3::new(1.0, 0.0, 2.0),
+            Vec3::new(4.0, 0.0, 0.0),
+            Vec3::new(1.0, 2.0, 0.0),
+            Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black()),
+        )
+    }
+
+    #[test]
+    fn uv_follows_the_edges() {
+        let quad = quad();
+        // corner + 0.25 * edge_u + 0.5 * edge_v
+        let target = Vec3::new(2.5, 1.0, 2.0);
+        let hit = quad.ray_intersect(&Ray::new(target - Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(hit.is_intersecting);
+        assert!((hit.distance - 5.0).abs() < 1e-4);
+        assert!((hit.u - 0.25).abs() < 1e-4 && (hit.v - 0.5).abs() < 1e-4);
+        assert!((hit.tangent - Vec3::new(1.0, 0.0, 0.0)).magnitude() < 1e-4);
+        // Desde -z se ve la cara trasera: la normal se invierte hacia el rayo
+        assert!(!hit.front_face);
+        assert!(hit.normal.z < 0.0);
+    }
+
+    #[test]
+    fn misses_outside_the_edges() {
+        let quad = quad();
+        // Dentro del rectángulo que lo contiene, pero fuera del paralelogramo
+        let outside = Vec3::new(1.2, 1.8, 2.0);
+        let hit = quad.ray_intersect(&Ray::new(outside - Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0)));
+        assert!(!hit.is_intersecting);
+        assert!(!quad.occluded(&Ray::new(outside - Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 1.0))));
+    }
+}
@@ -0,0 +1,69 @@
+use nalgebra_glm::Vec3;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Ray {
+    pub origin: Vec3,
+    pub direction: Vec3,
+    pub t_min: f32, // Solo cuentan las intersecciones con t_min < t < t_max
+    pub t_max: f32,
+    pub time: f32, // Instante dentro del obturador (0 = apertura, 1 = cierre)
+    pub cone_width: f32,  // Ancho del cono del píxel en el origen
+    pub cone_spread: f32, // Cuánto crece ese ancho por unidad de distancia recorrida
+    pub layers: u32, // Texeles transparentes que el rayo ya atravesó de largo
+}
+
+impl Ray {
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Ray {
+            origin,
+            direction,
+            t_min: 0.0,
+            t_max: f32::INFINITY,
+            time: 0.0,
+            cone_width: 0.0,
+            cone_spread: 0.0,
+            layers: 0,
+        }
+    }
+
+    pub fn with_range(origin: Vec3, direction: Vec3, t_min: f32, t_max: f32) -> Self {
+        Ray {
+            origin,
+            direction,
+            t_min,
+            t_max,
+            time: 0.0,
+            cone_width: 0.0,
+            cone_spread: 0.0,
+            layers: 0,
+        }
+    }
+
+    pub fn with_time(mut self, time: f32) -> Self {
+        self.time = time;
+        self
+    }
+
+    pub fn with_cone(mut self, width: f32, spread: f32) -> Self {
+        self.cone_width = width;
+        self.cone_spread = spread;
+        self
+    }
+
+    // Ancho que cubre el píxel a la distancia del parámetro t
+    pub fn footprint(&self, t: f32) -> f32 {
+        self.cone_width + self.cone_spread * t * self.direction.magnitude()
+    }
+
+    pub fn at(&self, t: f32) -> Vec3 {
+        self.origin + self.direction * t
+    }
+
+    pub fn in_range(&self, t: f32) -> bool {
+        t > self.t_min && t < self.t_max
+    }
+
+    pub fn inv_direction(&self) -> Vec3 {
+        Vec3::new(1.0 / self.direction.x, 1.0 / self.direction.y, 1.0 / self.direction.z)
+    }
+}
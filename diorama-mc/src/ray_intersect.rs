@@ -1,23 +1,37 @@
+use crate::aabb::Aabb;
+use crate::cube::Face;
 use crate::material::Material;
+use crate::ray::Ray;
 use nalgebra_glm::Vec3;
+use once_cell::sync::Lazy;
+use std::sync::Arc;
 
+static NO_MATERIAL: Lazy<Material> = Lazy::new(Material::black);
+
+// El material se toma prestado de la primitiva, así que un impacto no copia nada.
+// La normal siempre apunta contra el rayo; `front_face` indica si el rayo venía de afuera
 #[derive(Debug, Clone)]
-pub struct Intersect {
+pub struct Intersect<'a> {
     pub point: Vec3,
     pub normal: Vec3,
     pub distance: f32,
     pub is_intersecting: bool,
-    pub material: Material,
+    pub front_face: bool,
+    pub material: &'a Material,
     pub u: f32,
     pub v: f32,
+    pub tangent: Vec3,   // Dirección en que crece u sobre la superficie (cero si no se conoce)
+    pub bitangent: Vec3, // Dirección en que crece v
+    pub face: Option<Face>, // Cara del bloque golpeada, para los materiales con texturas por cara
+    pub uv_density: f32,    // Unidades de UV por unidad del mundo, para elegir el nivel de mipmap
 }
 
-impl Intersect {
+impl<'a> Intersect<'a> {
     pub fn new(
         point: Vec3,
         normal: Vec3,
         distance: f32,
-        material: Material,
+        material: &'a Material,
         u: f32,
         v: f32,
     ) -> Self {
@@ -26,25 +40,160 @@ impl Intersect {
             normal,
             distance,
             is_intersecting: true,
+            front_face: true,
             material,
             u,
             v,
+            tangent: Vec3::zeros(),
+            bitangent: Vec3::zeros(),
+            face: None,
+            uv_density: 1.0,
         }
     }
 
+    pub fn with_uv_density(mut self, uv_density: f32) -> Self {
+        self.uv_density = uv_density;
+        self
+    }
+
+    pub fn with_face(mut self, face: Face) -> Self {
+        self.face = Some(face);
+        self
+    }
+
+    // Marco tangente para los mapas de normales
+    pub fn with_tangents(mut self, tangent: Vec3, bitangent: Vec3) -> Self {
+        self.tangent = tangent;
+        self.bitangent = bitangent;
+        self
+    }
+
     pub fn empty() -> Self {
         Intersect {
             point: Vec3::new(0.0, 0.0, 0.0),
             normal: Vec3::new(0.0, 0.0, 0.0),
             distance: 0.0,
             is_intersecting: false,
-            material: Material::black(),
+            front_face: true,
+            material: &NO_MATERIAL,
             u: 0.0,
             v: 0.0,
+            tangent: Vec3::zeros(),
+            bitangent: Vec3::zeros(),
+            face: None,
+            uv_density: 1.0,
         }
     }
+
+    // Extremo de un tramo fuera de la escena (distancia infinita), sin superficie real
+    pub fn boundary(distance: f32) -> Self {
+        let mut intersect = Intersect::empty();
+        intersect.distance = distance;
+        intersect
+    }
+
+    // Orienta la normal contra el rayo a partir de la normal exterior de la superficie
+    pub fn set_face_normal(&mut self, ray_direction: &Vec3, outward_normal: &Vec3) {
+        self.front_face = ray_direction.dot(outward_normal) < 0.0;
+        self.normal = if self.front_face {
+            *outward_normal
+        } else {
+            -outward_normal
+        };
+    }
 }
 
+// Tramo del rayo que queda dentro de un sólido. Ambos impactos llevan la normal exterior;
+// una salida con distancia infinita indica que el tramo sigue más allá del rango del rayo
+#[derive(Debug, Clone)]
+pub struct Span<'a> {
+    pub enter: Intersect<'a>,
+    pub exit: Intersect<'a>,
+}
+
+// Separación entre impactos consecutivos al recorrer un sólido por marcha
+const SPAN_STEP: f32 = 1e-4;
+const MAX_SPAN_HITS: usize = 64;
+
 pub trait RayIntersect {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect;
-}
\ No newline at end of file
+    // Intersección más cercana dentro del rango del rayo
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_>;
+
+    // Consulta de oclusión: basta con cualquier intersección dentro del rango
+    fn occluded(&self, ray: &Ray) -> bool {
+        self.occluder(ray).is_some()
+    }
+
+    // Material de algo que tapa el rayo dentro del rango, sin buscar el impacto más cercano.
+    // Si hay varios candidatos se prefiere uno opaco: con él la sombra ya es completa
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        let intersect = self.ray_intersect(ray);
+        intersect.is_intersecting.then_some(intersect.material)
+    }
+
+    fn bounding_box(&self) -> Aabb;
+
+    // Tramos del rayo dentro del objeto, ordenados, a partir de `t_min`; los usa la CSG.
+    // Por defecto se obtienen encadenando impactos: una cara frontal abre un tramo y una trasera lo cierra
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let mut spans = Vec::new();
+        let mut enter: Option<Intersect> = None;
+        let mut current = *ray;
+
+        for _ in 0..MAX_SPAN_HITS {
+            let mut hit = self.ray_intersect(&current);
+            if !hit.is_intersecting {
+                break;
+            }
+            current.t_min = hit.distance + SPAN_STEP;
+
+            if hit.front_face {
+                enter = Some(hit);
+            } else {
+                // Si no hubo entrada, el rayo empezó dentro del objeto
+                hit.normal = -hit.normal;
+                let start = enter.take().unwrap_or_else(|| Intersect::boundary(f32::NEG_INFINITY));
+                spans.push(Span { enter: start, exit: hit });
+            }
+        }
+
+        if let Some(start) = enter {
+            spans.push(Span { enter: start, exit: Intersect::boundary(f32::INFINITY) });
+        }
+        spans
+    }
+}
+
+impl<T: RayIntersect + ?Sized> RayIntersect for Box<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        (**self).ray_intersect(ray)
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        (**self).occluder(ray)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        (**self).spans(ray)
+    }
+}
+
+impl<T: RayIntersect + ?Sized> RayIntersect for Arc<T> {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        (**self).ray_intersect(ray)
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        (**self).occluder(ray)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        (**self).bounding_box()
+    }
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        (**self).spans(ray)
+    }
+}
@@ -0,0 +1,66 @@
+use crate::aabb::Aabb;
+use crate::bvh::Bvh;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+
+pub type SceneObject = Box<dyn RayIntersect + Send + Sync>;
+
+// Escena heterogénea: cualquier primitiva que implemente RayIntersect, organizada en un BVH.
+// Las primitivas sin límites (planos infinitos) quedan fuera del BVH y se prueban una por una
+pub struct Scene {
+    bvh: Bvh<SceneObject>,
+    unbounded: Vec<SceneObject>,
+}
+
+impl Scene {
+    pub fn new(objects: Vec<SceneObject>) -> Self {
+        let (bounded, unbounded): (Vec<SceneObject>, Vec<SceneObject>) =
+            objects.into_iter().partition(|o| o.bounding_box().is_finite());
+        Scene {
+            bvh: Bvh::new(bounded),
+            unbounded,
+        }
+    }
+
+    pub fn objects(&self) -> impl Iterator<Item = &SceneObject> {
+        self.bvh.objects().iter().chain(self.unbounded.iter())
+    }
+}
+
+impl RayIntersect for Scene {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let mut ray = *ray;
+        let mut intersect = self.bvh.ray_intersect(&ray);
+        if intersect.is_intersecting {
+            ray.t_max = intersect.distance;
+        }
+        for object in &self.unbounded {
+            let i = object.ray_intersect(&ray);
+            if i.is_intersecting && i.distance < ray.t_max {
+                ray.t_max = i.distance;
+                intersect = i;
+            }
+        }
+        intersect
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        let mut occluder = self.bvh.occluder(ray);
+        for object in &self.unbounded {
+            if occluder.is_some_and(Material::is_opaque) {
+                break;
+            }
+            occluder = object.occluder(ray).or(occluder);
+        }
+        occluder
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        if self.unbounded.is_empty() {
+            self.bvh.bounding_box()
+        } else {
+            Aabb::infinite()
+        }
+    }
+}
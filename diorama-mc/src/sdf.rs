@@ -0,0 +1,231 @@
+use crate::aabb::Aabb;
+use crate::cube::{box_uv, slab_range, Face};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::{Vec2, Vec3};
+
+const MAX_STEPS: usize = 256;
+const HIT_EPSILON: f32 = 1e-4;
+const NORMAL_EPSILON: f32 = 1e-3;
+
+// Función de distancia con signo construida a partir de formas básicas y operaciones
+#[derive(Debug, Clone)]
+pub enum Sdf {
+    Sphere(f32), // radio
+    Box(Vec3), // medias dimensiones
+    Torus(f32, f32), // radio mayor, radio menor (en el plano XZ)
+    Translate(Vec3, Box<Sdf>),
+    Round(f32, Box<Sdf>), // redondea las aristas inflando la forma
+    Union(Box<Sdf>, Box<Sdf>),
+    SmoothUnion(Box<Sdf>, Box<Sdf>, f32), // k: ancho de la mezcla
+    Repeat(Vec3, Box<Sdf>), // repite la forma cada `period` unidades
+}
+
+impl Sdf {
+    pub fn sphere(radius: f32) -> Sdf {
+        Sdf::Sphere(radius)
+    }
+
+    pub fn cuboid(half_extents: Vec3) -> Sdf {
+        Sdf::Box(half_extents)
+    }
+
+    pub fn torus(major_radius: f32, minor_radius: f32) -> Sdf {
+        Sdf::Torus(major_radius, minor_radius)
+    }
+
+    pub fn translate(self, offset: Vec3) -> Sdf {
+        Sdf::Translate(offset, Box::new(self))
+    }
+
+    pub fn round(self, radius: f32) -> Sdf {
+        Sdf::Round(radius, Box::new(self))
+    }
+
+    pub fn union(self, other: Sdf) -> Sdf {
+        Sdf::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Sdf, k: f32) -> Sdf {
+        Sdf::SmoothUnion(Box::new(self), Box::new(other), k)
+    }
+
+    pub fn repeat(self, period: Vec3) -> Sdf {
+        Sdf::Repeat(period, Box::new(self))
+    }
+
+    pub fn distance(&self, p: &Vec3) -> f32 {
+        match self {
+            Sdf::Sphere(radius) => p.magnitude() - radius,
+            Sdf::Box(half_extents) => {
+                let q = p.abs() - half_extents;
+                q.sup(&Vec3::zeros()).magnitude() + q.x.max(q.y.max(q.z)).min(0.0)
+            }
+            Sdf::Torus(major, minor) => {
+                let q = Vec2::new(Vec2::new(p.x, p.z).magnitude() - major, p.y);
+                q.magnitude() - minor
+            }
+            Sdf::Translate(offset, shape) => shape.distance(&(p - offset)),
+            Sdf::Round(radius, shape) => shape.distance(p) - radius,
+            Sdf::Union(a, b) => a.distance(p).min(b.distance(p)),
+            Sdf::SmoothUnion(a, b, k) => {
+                let d1 = a.distance(p);
+                let d2 = b.distance(p);
+                let h = (0.5 + 0.5 * (d2 - d1) / k).clamp(0.0, 1.0);
+                d2 + (d1 - d2) * h - k * h * (1.0 - h)
+            }
+            Sdf::Repeat(period, shape) => {
+                let q = Vec3::new(
+                    p.x - period.x * (p.x / period.x).round(),
+                    p.y - period.y * (p.y / period.y).round(),
+                    p.z - period.z * (p.z / period.z).round(),
+                );
+                shape.distance(&q)
+            }
+        }
+    }
+
+    // Normal como gradiente de la distancia (diferencias centrales)
+    pub fn normal(&self, p: &Vec3) -> Vec3 {
+        let dx = Vec3::new(NORMAL_EPSILON, 0.0, 0.0);
+        let dy = Vec3::new(0.0, NORMAL_EPSILON, 0.0);
+        let dz = Vec3::new(0.0, 0.0, NORMAL_EPSILON);
+        Vec3::new(
+            self.distance(&(p + dx)) - self.distance(&(p - dx)),
+            self.distance(&(p + dy)) - self.distance(&(p - dy)),
+            self.distance(&(p + dz)) - self.distance(&(p - dz)),
+        )
+        .normalize()
+    }
+}
+
+// Primitiva trazada con sphere tracing; `bounds` limita la marcha y se usa en el BVH
+pub struct SdfObject {
+    pub sdf: Sdf,
+    pub bounds: Aabb,
+    pub material: Material,
+}
+
+impl SdfObject {
+    pub fn new(sdf: Sdf, bounds: Aabb, material: Material) -> Self {
+        SdfObject {
+            sdf,
+            bounds,
+            material,
+        }
+    }
+
+    fn march(&self, ray: &Ray) -> Option<f32> {
+        let (t_enter, t_exit) = slab_range(&self.bounds.min, &self.bounds.max, ray)?;
+        let t_exit = t_exit.min(ray.t_max);
+
+        // Los pasos se miden en distancia real, así que se escalan si la dirección no es unitaria
+        let speed = ray.direction.magnitude();
+        let mut t = t_enter.max(ray.t_min);
+
+        // Si el rayo nace dentro de la forma, primero se sale de ella
+        let inside = self.sdf.distance(&ray.at(t)) < 0.0;
+
+        for _ in 0..MAX_STEPS {
+            if t > t_exit {
+                return None;
+            }
+            let distance = self.sdf.distance(&ray.at(t));
+            let distance = if inside { -distance } else { distance };
+            if distance < HIT_EPSILON * t.max(1.0) {
+                return if ray.in_range(t) { Some(t) } else { None };
+            }
+            t += distance / speed;
+        }
+        None
+    }
+}
+
+impl RayIntersect for SdfObject {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.march(ray) {
+            Some(t) => {
+                let point = ray.at(t);
+                let normal = self.sdf.normal(&point);
+
+                // Proyección de caja: la textura entra por la cara del volumen que más mira la normal
+                let abs = normal.abs();
+                let mut axis_normal = Vec3::zeros();
+                let axis = if abs.x >= abs.y && abs.x >= abs.z {
+                    0
+                } else if abs.y >= abs.z {
+                    1
+                } else {
+                    2
+                };
+                axis_normal[axis] = normal[axis].signum();
+                let mapping = self.material.uv_mapping();
+                let (u, v) = box_uv(mapping, &self.bounds.min, &self.bounds.max, &point, &axis_normal);
+
+                let mut intersect =
+                    Intersect::new(point, normal, t, &self.material, u, v).with_face(Face::from_normal(&axis_normal));
+                intersect.set_face_normal(&ray.direction, &normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.march(ray).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::color::Color;
+
+    fn object(sdf: Sdf, bounds: Aabb) -> SdfObject {
+        let material = Material::new(Color::black(), 0.0, [1.0, 0.0, 0.0, 0.0], 1.0, Color::black());
+        SdfObject::new(sdf, bounds, material)
+    }
+
+    #[test]
+    fn marches_to_the_surface() {
+        let center = Vec3::new(1.0, 2.0, 3.0);
+        let sphere = object(
+            Sdf::sphere(1.0).translate(center),
+            Aabb::new(center - Vec3::new(1.0, 1.0, 1.0), center + Vec3::new(1.0, 1.0, 1.0)),
+        );
+        // Dirección sin normalizar: la distancia se mide igual en el parámetro del rayo
+        let ray = Ray::new(center - Vec3::new(0.0, 0.0, 5.0), Vec3::new(0.0, 0.0, 2.0));
+        let hit = sphere.ray_intersect(&ray);
+        assert!(hit.is_intersecting && hit.front_face);
+        assert!((hit.distance - 2.0).abs() < 1e-3);
+        assert!((hit.normal - Vec3::new(0.0, 0.0, -1.0)).magnitude() < 1e-3);
+
+        // Desde adentro se sale por el otro lado
+        let inside = sphere.ray_intersect(&Ray::new(center, Vec3::new(0.0, 0.0, 1.0)));
+        assert!(inside.is_intersecting && !inside.front_face);
+        assert!((inside.distance - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn distances_of_combined_shapes() {
+        // Una caja redondeada crece por todos lados en el radio del redondeo
+        let rounded = Sdf::cuboid(Vec3::new(1.0, 1.0, 1.0)).round(0.25);
+        assert!((rounded.distance(&Vec3::new(3.0, 0.0, 0.0)) - 1.75).abs() < 1e-6);
+
+        // La repetición copia la forma cada periodo
+        let repeated = Sdf::sphere(0.5).repeat(Vec3::new(4.0, 4.0, 4.0));
+        assert!((repeated.distance(&Vec3::new(8.0, 4.0, 1.0)) - 0.5).abs() < 1e-6);
+
+        // La unión suave nunca queda por encima de la unión común
+        let a = Sdf::sphere(1.0);
+        let b = Sdf::torus(2.0, 0.5);
+        let p = Vec3::new(1.2, 0.3, 0.0);
+        let sharp = a.clone().union(b.clone()).distance(&p);
+        assert!(a.smooth_union(b, 0.5).distance(&p) < sharp);
+    }
+}
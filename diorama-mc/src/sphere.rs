@@ -1,11 +1,16 @@
 use nalgebra_glm::Vec3; 
-use crate::ray_intersect::{Intersect, RayIntersect};
+use crate::aabb::Aabb;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect, Span};
 use crate::material::Material; 
+use crate::motion::Motion;
+use crate::transform::Frame;
 
 pub struct Sphere {
     pub center: Vec3,
     pub radius: f32,
     pub material: Material,
+    pub motion: Option<Motion>, // Movimiento durante el obturador; `center` es la posición en reposo
 }
 
 impl Sphere {
@@ -15,16 +20,32 @@ impl Sphere {
         let v = 0.5 - (normalized.y.asin() / std::f32::consts::PI);
         (u, v)
     }
+
+    // Directions in which u (longitude) and v (towards the south pole) grow; undefined at the poles
+    fn get_tangents(&self, normal: &Vec3) -> (Vec3, Vec3) {
+        let radius_xz = (normal.x * normal.x + normal.z * normal.z).sqrt();
+        if radius_xz < 1e-6 {
+            return (Vec3::zeros(), Vec3::zeros());
+        }
+        let tangent = Vec3::new(-normal.z, 0.0, normal.x) / radius_xz;
+        let bitangent = Vec3::new(
+            normal.y * normal.x / radius_xz,
+            -radius_xz,
+            normal.y * normal.z / radius_xz,
+        );
+        (tangent, bitangent)
+    }
 }
 
-impl RayIntersect for Sphere {
-    fn ray_intersect(&self, ray_origin: &Vec3, ray_direction: &Vec3) -> Intersect {
+impl Sphere {
+    // Both roots of the ray/sphere equation (near, far), ignoring the ray's range
+    fn roots(&self, ray: &Ray) -> Option<(f32, f32)> {
         // Vector from the ray origin to the center of the sphere
-        let oc = *ray_origin - self.center;
+        let oc = ray.origin - self.center;
 
         // Coefficients for the quadratic equation
-        let a = ray_direction.dot(ray_direction);
-        let b = 2.0 * oc.dot(ray_direction);
+        let a = ray.direction.dot(&ray.direction);
+        let b = 2.0 * oc.dot(&ray.direction);
         let c = oc.dot(&oc) - self.radius * self.radius;
 
         // Calculate the discriminant
@@ -32,22 +53,98 @@ impl RayIntersect for Sphere {
 
         // If the discriminant is greater than 0, the ray intersects the sphere
         if discriminant > 0.0 {
-            // Calculate the nearest point of intersection
-            let t = (-b - discriminant.sqrt()) / (2.0 * a);
-            if t > 0.0 {
-                // Compute intersection point, normal at the intersection, and distance from the ray origin
-                let point = ray_origin + ray_direction * t;
-                let normal = (point - self.center).normalize();
-                let distance = t;
-                let (u, v) = self.get_uv(&point);
-
-                return Intersect::new(point, normal, distance, self.material.clone(), u, v);
+            let sqrt_discriminant = discriminant.sqrt();
+            let near = (-b - sqrt_discriminant) / (2.0 * a);
+            let far = (-b + sqrt_discriminant) / (2.0 * a);
+            return Some((near, far));
+        }
+        None
+    }
+
+    // Distance to the nearest intersection inside the ray's range, if any
+    fn nearest_root(&self, ray: &Ray) -> Option<f32> {
+        // Try the near root first; if the ray starts inside, the far root is the exit point
+        let (near, far) = self.roots(ray)?;
+        if ray.in_range(near) {
+            Some(near)
+        } else if ray.in_range(far) {
+            Some(far)
+        } else {
+            None
+        }
+    }
+
+    // Hit on the surface at distance `t`, with the outward normal
+    fn surface_hit(&self, ray: &Ray, t: f32) -> Intersect<'_> {
+        // Compute intersection point, normal at the intersection, and distance from the ray origin
+        let point = ray.at(t);
+        let normal = (point - self.center).normalize();
+        let distance = t;
+        let (u, v) = self.get_uv(&point);
+        let (tangent, bitangent) = self.get_tangents(&normal);
+
+        // u recorre el ecuador (2πr) y v un meridiano (πr); se usa la media geométrica
+        let uv_density = 1.0 / (std::f32::consts::PI * self.radius * std::f32::consts::SQRT_2);
+
+        Intersect::new(point, normal, distance, &self.material, u, v)
+            .with_tangents(tangent, bitangent)
+            .with_uv_density(uv_density)
+    }
+
+    // Frame of the sphere at the ray's time, if it is moving
+    fn frame(&self, ray: &Ray) -> Option<Frame> {
+        self.motion.map(|motion| motion.frame_at(ray.time, &self.center))
+    }
+}
+
+impl RayIntersect for Sphere {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let frame = self.frame(ray);
+        let local = frame.as_ref().map_or(*ray, |frame| frame.to_local(ray));
+        if let Some(t) = self.nearest_root(&local) {
+            let mut intersect = self.surface_hit(&local, t);
+            if let Some(frame) = &frame {
+                frame.to_world(&mut intersect);
             }
+            let normal = intersect.normal;
+            intersect.set_face_normal(&ray.direction, &normal);
+            return intersect;
         }
-        
+
         // If no intersection, return an empty intersect
         Intersect::empty()
-        
+    }
 
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        let local = self.frame(ray).map_or(*ray, |frame| frame.to_local(ray));
+        self.nearest_root(&local).map(|_| &self.material)
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+        let rest = Aabb::new(self.center - extent, self.center + extent);
+        match &self.motion {
+            Some(motion) => motion.bounds(&rest, &self.center),
+            None => rest,
+        }
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let frame = self.frame(ray);
+        let local = frame.as_ref().map_or(*ray, |frame| frame.to_local(ray));
+        match self.roots(&local) {
+            Some((near, far)) if far > local.t_min => {
+                let mut span = Span {
+                    enter: self.surface_hit(&local, near),
+                    exit: self.surface_hit(&local, far),
+                };
+                if let Some(frame) = &frame {
+                    frame.to_world(&mut span.enter);
+                    frame.to_world(&mut span.exit);
+                }
+                vec![span]
+            }
+            _ => Vec::new(),
+        }
     }
 }
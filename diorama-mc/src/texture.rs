@@ -1,63 +1,263 @@
 extern crate image;
+use crate::animation::Animation;
 use crate::color::Color;
 use image::{DynamicImage, GenericImageView, ImageReader, Pixel};
+use nalgebra_glm::{Vec3, Vec4};
 use std::fmt;
 
+// Filtro con el que se lee la textura. Todos eligen el nivel de mipmap según el LOD, así que
+// de cerca (LOD 0) Nearest conserva los píxeles nítidos de Minecraft
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Filter {
+    #[default]
+    Nearest,   // Texel más cercano del nivel más cercano
+    Bilinear,  // Mezcla de los 4 texeles vecinos del nivel más cercano
+    Trilinear, // Bilineal en los dos niveles que rodean el LOD, mezclados entre sí
+}
+
+// Qué se lee fuera del rango 0..1 de la UV
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Wrap {
+    #[default]
+    Clamp,  // Se repite el texel del borde (bloques y atlas)
+    Repeat, // La textura se repite como un mosaico (p. ej. las UV de los modelos OBJ)
+}
+
+impl Wrap {
+    // Coordenada de textura dentro de 0..1
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Wrap::Clamp => t.clamp(0.0, 1.0),
+            Wrap::Repeat => t.rem_euclid(1.0),
+        }
+    }
+
+    // Texel de una fila o columna de `size` texeles para un índice que puede caer fuera de ella
+    fn texel(self, index: f32, size: usize) -> usize {
+        match self {
+            Wrap::Clamp => (index.max(0.0) as usize).min(size - 1),
+            Wrap::Repeat => (index as i64).rem_euclid(size as i64) as usize,
+        }
+    }
+}
+
+// Nivel de la cadena de mipmaps de un cuadro, la mitad de ancho y alto que el anterior
+#[derive(Clone)]
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<Color>,
+    alpha: Vec<u8>,
+}
+
 #[derive(Clone)]
 pub struct Texture {
     pub id: String,
     image: DynamicImage,
-    pub width: usize,
+    pub width: usize,  // Tamaño de un cuadro (de la imagen completa si no está animada)
     pub height: usize,
-    color_array: Vec<Color>,
+    frames: Vec<Vec<MipLevel>>, // Cadena de mipmaps de cada cuadro; el nivel 0 es el cuadro original
+    pub has_alpha: bool,        // Algún texel no es completamente opaco
+    pub animation: Option<Animation>,
 }
 
 impl Texture {
+    // Si junto a la imagen hay un `.mcmeta` con animación, la textura queda animada
     pub fn new(file_path: &str) -> Texture {
         let img = ImageReader::open(file_path)
             .expect("Failed to open file")
             .decode()
             .expect("Failed to decode image");
+        match Animation::load_mcmeta(file_path, img.width() as usize, img.height() as usize) {
+            Some((animation, width, height)) => Texture::from_frames(file_path, img, width, height, Some(animation)),
+            None => Texture::from_image(file_path, img),
+        }
+    }
 
-        let width = img.width() as usize;
-        let height = img.height() as usize;
+    // Textura a partir de una imagen ya cargada (p. ej. un atlas armado en memoria)
+    pub fn from_image(id: &str, img: DynamicImage) -> Texture {
+        let (width, height) = (img.width() as usize, img.height() as usize);
+        Texture::from_frames(id, img, width, height, None)
+    }
 
+    // Imagen con cuadros de `width` x `height` apilados de arriba a abajo
+    pub fn from_frames(id: &str, img: DynamicImage, width: usize, height: usize, animation: Option<Animation>) -> Texture {
         // Verificar que la imagen no tenga dimensiones nulas
         assert!(
             width > 0 && height > 0,
             "Image is empty or not loaded correctly"
         );
+        let frame_count = (img.height() as usize / height).max(1);
 
         let mut texture = Texture {
-            id: file_path.to_string(),
+            id: id.to_string(),
             image: img,
             width,
             height,
-            color_array: vec![Color::black(); width * height],
+            frames: Vec::with_capacity(frame_count),
+            has_alpha: false,
+            animation,
         };
-        texture.load_color_array();
+        for frame in 0..frame_count {
+            let level = texture.load_frame(frame);
+            texture.has_alpha |= level.alpha.iter().any(|&alpha| alpha < 255);
+            texture.frames.push(build_mips(level));
+        }
         texture
     }
 
-    fn load_color_array(&mut self) {
+    fn load_frame(&self, frame: usize) -> MipLevel {
+        let mut texels = vec![Color::black(); self.width * self.height];
+        let mut alpha = vec![255; self.width * self.height];
         for x in 0..self.width {
             for y in 0..self.height {
-                let pixel = self.image.get_pixel(x as u32, y as u32).to_rgb();
+                let pixel = self.image.get_pixel(x as u32, (frame * self.height + y) as u32).to_rgba();
                 let color =
                     ((pixel[0] as u32) << 16) | ((pixel[1] as u32) << 8) | (pixel[2] as u32);
-                self.color_array[y * self.width + x] = Color::from_hex(color);
+                texels[y * self.width + x] = Color::from_hex(color);
+                alpha[y * self.width + x] = pixel[3];
             }
         }
+        MipLevel {
+            width: self.width,
+            height: self.height,
+            texels,
+            alpha,
+        }
+    }
+
+    pub fn image(&self) -> &DynamicImage {
+        &self.image
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Número de niveles, contando la imagen original
+    pub fn mip_levels(&self) -> usize {
+        self.frames[0].len()
+    }
+
+    fn level(&self, frame: usize, level: usize) -> &MipLevel {
+        let levels = &self.frames[frame.min(self.frames.len() - 1)];
+        &levels[level.min(levels.len() - 1)]
+    }
+
+    // Lectura filtrada en el instante `clock` (segundos). `lod` es log2 de cuántos texeles
+    // del nivel 0 cubre el píxel
+    pub fn sample(&self, u: f32, v: f32, filter: Filter, wrap: Wrap, lod: f32, clock: f32) -> Color {
+        self.sample_rgba(u, v, filter, wrap, lod, clock).0
+    }
+
+    // Igual que `sample`, junto con el alfa (0 = transparente, 1 = opaco)
+    pub fn sample_rgba(&self, u: f32, v: f32, filter: Filter, wrap: Wrap, lod: f32, clock: f32) -> (Color, f32) {
+        let rgba = match &self.animation {
+            Some(animation) => {
+                let (current, next, blend) = animation.frame_at(clock);
+                let rgba = self.sample_frame(current, u, v, filter, wrap, lod);
+                if blend > 0.0 {
+                    rgba + (self.sample_frame(next, u, v, filter, wrap, lod) - rgba) * blend
+                } else {
+                    rgba
+                }
+            }
+            None => self.sample_frame(0, u, v, filter, wrap, lod),
+        };
+        (to_color(&rgba.xyz()), rgba.w / 255.0)
+    }
+
+    fn sample_frame(&self, frame: usize, u: f32, v: f32, filter: Filter, wrap: Wrap, lod: f32) -> Vec4 {
+        let lod = lod.clamp(0.0, (self.mip_levels() - 1) as f32);
+        match filter {
+            Filter::Nearest => self.nearest(self.level(frame, lod.round() as usize), u, v, wrap),
+            Filter::Bilinear => self.bilinear(self.level(frame, lod.round() as usize), u, v, wrap),
+            Filter::Trilinear => {
+                let level = lod.floor() as usize;
+                let weight = lod.fract();
+                let fine = self.bilinear(self.level(frame, level), u, v, wrap);
+                let coarse = self.bilinear(self.level(frame, level + 1), u, v, wrap);
+                fine + (coarse - fine) * weight
+            }
+        }
+    }
+
+    // Texel que contiene la UV (v crece hacia arriba, la imagen empieza arriba)
+    fn nearest(&self, level: &MipLevel, u: f32, v: f32, wrap: Wrap) -> Vec4 {
+        let x = (wrap.apply(u) * level.width as f32) as usize;
+        let y = ((1.0 - wrap.apply(v)) * level.height as f32) as usize;
+        let index = y.min(level.height - 1) * level.width + x.min(level.width - 1);
+        let color = level.texels[index];
+        Vec4::new(color.r() as f32, color.g() as f32, color.b() as f32, level.alpha[index] as f32)
+    }
+
+    // Interpolación entre los centros de los 4 texeles más cercanos; en los bordes se repite el
+    // texel del borde o, con Wrap::Repeat, se mezcla con el del lado opuesto
+    fn bilinear(&self, level: &MipLevel, u: f32, v: f32, wrap: Wrap) -> Vec4 {
+        let (width, height) = (level.width, level.height);
+        let x = wrap.apply(u) * width as f32 - 0.5;
+        let y = (1.0 - wrap.apply(v)) * height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let texel = |tx: f32, ty: f32| {
+            let tx = wrap.texel(tx, width);
+            let ty = wrap.texel(ty, height);
+            let index = ty * width + tx;
+            let color = level.texels[index];
+            Vec4::new(color.r() as f32, color.g() as f32, color.b() as f32, level.alpha[index] as f32)
+        };
+        let (c00, c10) = (texel(x0, y0), texel(x0 + 1.0, y0));
+        let (c01, c11) = (texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0));
+        let top = c00 + (c10 - c00) * fx;
+        let bottom = c01 + (c11 - c01) * fx;
+        top + (bottom - top) * fy
     }
 
     pub fn get_color(&self, x: usize, y: usize) -> Color {
         if x < self.width && y < self.height {
-            self.color_array[y * self.width + x]
+            self.frames[0][0].texels[y * self.width + x]
         } else {
             Color::from_hex(0xFF00FF) // Magenta para indicar error de coordenadas
         }
     }
 
+    // Color medio de los texeles visibles del primer cuadro: el último nivel de mipmap ya lo
+    // tiene ponderado por el alfa
+    pub fn average_color(&self) -> Color {
+        self.level(0, self.mip_levels() - 1).texels[0]
+    }
+
+    // Copia donde solo quedan los texeles con luminancia de al menos `threshold` (0 a 1) y el
+    // resto queda en negro; sirve como mapa de emisión para que brillen solo las partes claras.
+    // Conserva la animación, así que el brillo sigue a cada cuadro
+    pub fn emissive_mask(&self, threshold: f32) -> Texture {
+        let mut mask = self.image.to_rgba8();
+        for pixel in mask.pixels_mut() {
+            let luminance =
+                (0.2126 * pixel[0] as f32 + 0.7152 * pixel[1] as f32 + 0.0722 * pixel[2] as f32) / 255.0;
+            if luminance < threshold {
+                pixel[0] = 0;
+                pixel[1] = 0;
+                pixel[2] = 0;
+            }
+        }
+        Texture::from_frames(
+            &format!("{}#emissive>{}", self.id, threshold),
+            DynamicImage::ImageRgba8(mask),
+            self.width,
+            self.height,
+            self.animation.clone(),
+        )
+    }
+
+    pub fn get_alpha(&self, x: usize, y: usize) -> u8 {
+        if x < self.width && y < self.height {
+            self.frames[0][0].alpha[y * self.width + x]
+        } else {
+            255
+        }
+    }
+
     pub fn get_color_at_uv(&self, u: f32, v: f32) -> Color {
         // Asegúrate de que u y v estén en el rango [0, 1]
         let u = u.clamp(0.0, 1.0);
@@ -72,6 +272,57 @@ impl Texture {
     }
 }
 
+// Cada nivel promedia bloques de 2x2 del anterior hasta llegar a 1x1. El color se pondera
+// por el alfa para que los huecos transparentes (casi siempre negros) no oscurezcan los bordes
+fn build_mips(base: MipLevel) -> Vec<MipLevel> {
+    let mut levels = vec![base];
+    loop {
+        let previous = levels.last().unwrap();
+        let (width, height) = (previous.width, previous.height);
+        if width <= 1 && height <= 1 {
+            return levels;
+        }
+        let next_width = (width / 2).max(1);
+        let next_height = (height / 2).max(1);
+        let mut texels = Vec::with_capacity(next_width * next_height);
+        let mut alpha = Vec::with_capacity(next_width * next_height);
+        for y in 0..next_height {
+            for x in 0..next_width {
+                let mut sum = Vec3::zeros();
+                let mut plain = Vec3::zeros();
+                let mut coverage = 0.0;
+                for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let sx = (x * 2 + dx).min(width - 1);
+                    let sy = (y * 2 + dy).min(height - 1);
+                    let color = previous.texels[sy * width + sx];
+                    let color = Vec3::new(color.r() as f32, color.g() as f32, color.b() as f32);
+                    let a = previous.alpha[sy * width + sx] as f32;
+                    sum += color * a;
+                    plain += color;
+                    coverage += a;
+                }
+                let color = if coverage > 0.0 { sum / coverage } else { plain / 4.0 };
+                texels.push(to_color(&color));
+                alpha.push((coverage / 4.0).round() as u8);
+            }
+        }
+        levels.push(MipLevel {
+            width: next_width,
+            height: next_height,
+            texels,
+            alpha,
+        });
+    }
+}
+
+fn to_color(rgb: &Vec3) -> Color {
+    Color::new(
+        rgb.x.round().clamp(0.0, 255.0) as u8,
+        rgb.y.round().clamp(0.0, 255.0) as u8,
+        rgb.z.round().clamp(0.0, 255.0) as u8,
+    )
+}
+
 impl fmt::Debug for Texture {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Texture")
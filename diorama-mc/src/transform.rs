@@ -0,0 +1,149 @@
+use crate::aabb::Aabb;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect, Span};
+use nalgebra_glm::{self as glm, Mat3, Mat4, Vec3, Vec4};
+use std::sync::Arc;
+
+// Matriz de un objeto junto con lo necesario para llevar rayos a su espacio y traer los impactos de vuelta
+#[derive(Debug, Clone)]
+pub struct Frame {
+    matrix: Mat4,
+    inverse: Mat4,
+    normal_matrix: Mat3,
+    scale: f32, // Escala lineal media (raíz cúbica del determinante)
+}
+
+impl Frame {
+    pub fn new(matrix: Mat4) -> Self {
+        let inverse = matrix.try_inverse().expect("Transform matrix is not invertible");
+        // Las normales se transforman con la inversa transpuesta
+        let normal_matrix = glm::mat4_to_mat3(&inverse).transpose();
+        let scale = glm::mat4_to_mat3(&matrix).determinant().abs().cbrt();
+        Frame {
+            matrix,
+            inverse,
+            normal_matrix,
+            scale,
+        }
+    }
+
+    #[allow(dead_code)]
+    pub fn matrix(&self) -> &Mat4 {
+        &self.matrix
+    }
+
+    // La dirección no se normaliza para que la distancia t (y el rango) sea la misma en ambos espacios.
+    // El resto del rayo (rango, instante, cono, capas) se copia tal cual
+    pub fn to_local(&self, ray: &Ray) -> Ray {
+        let mut local = *ray;
+        local.origin = transform_point(&self.inverse, &ray.origin);
+        local.direction = transform_vector(&self.inverse, &ray.direction);
+        local
+    }
+
+    pub fn to_world(&self, intersect: &mut Intersect) {
+        if intersect.is_intersecting {
+            intersect.point = transform_point(&self.matrix, &intersect.point);
+            intersect.normal = (self.normal_matrix * intersect.normal).normalize();
+            intersect.tangent = transform_vector(&self.matrix, &intersect.tangent);
+            intersect.bitangent = transform_vector(&self.matrix, &intersect.bitangent);
+            intersect.uv_density /= self.scale;
+        }
+    }
+
+    // Caja alineada que contiene las 8 esquinas transformadas
+    pub fn transform_box(&self, local: &Aabb) -> Aabb {
+        if !local.is_finite() {
+            return *local;
+        }
+
+        let mut bounds = Aabb::empty();
+        for i in 0..8 {
+            let corner = Vec3::new(
+                if i & 1 == 0 { local.min.x } else { local.max.x },
+                if i & 2 == 0 { local.min.y } else { local.max.y },
+                if i & 4 == 0 { local.min.z } else { local.max.z },
+            );
+            bounds.grow(&transform_point(&self.matrix, &corner));
+        }
+        bounds
+    }
+}
+
+fn transform_point(matrix: &Mat4, point: &Vec3) -> Vec3 {
+    let p = matrix * Vec4::new(point.x, point.y, point.z, 1.0);
+    Vec3::new(p.x, p.y, p.z) / p.w
+}
+
+fn transform_vector(matrix: &Mat4, vector: &Vec3) -> Vec3 {
+    let v = matrix * Vec4::new(vector.x, vector.y, vector.z, 0.0);
+    Vec3::new(v.x, v.y, v.z)
+}
+
+// Instancia de una geometría compartida con su propia matriz de transformación.
+// Los rayos se llevan al espacio del objeto y el resultado se devuelve al espacio del mundo
+#[allow(dead_code)]
+pub struct Transform {
+    pub object: Arc<dyn RayIntersect + Send + Sync>,
+    frame: Frame,
+}
+
+#[allow(dead_code)]
+impl Transform {
+    pub fn new(object: Arc<dyn RayIntersect + Send + Sync>) -> Self {
+        Transform::with_matrix(object, Mat4::identity())
+    }
+
+    pub fn with_matrix(object: Arc<dyn RayIntersect + Send + Sync>, matrix: Mat4) -> Self {
+        Transform {
+            object,
+            frame: Frame::new(matrix),
+        }
+    }
+
+    // Cada operación se aplica después de las anteriores (en espacio del mundo)
+    pub fn translate(self, offset: Vec3) -> Self {
+        let matrix = glm::translation(&offset) * self.frame.matrix;
+        Transform::with_matrix(self.object, matrix)
+    }
+
+    pub fn rotate(self, angle: f32, axis: Vec3) -> Self {
+        let matrix = glm::rotation(angle, &axis) * self.frame.matrix;
+        Transform::with_matrix(self.object, matrix)
+    }
+
+    pub fn scale(self, factors: Vec3) -> Self {
+        let matrix = glm::scaling(&factors) * self.frame.matrix;
+        Transform::with_matrix(self.object, matrix)
+    }
+
+    pub fn matrix(&self) -> &Mat4 {
+        self.frame.matrix()
+    }
+}
+
+impl RayIntersect for Transform {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        let mut intersect = self.object.ray_intersect(&self.frame.to_local(ray));
+        self.frame.to_world(&mut intersect);
+        intersect
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.object.occluder(&self.frame.to_local(ray))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        self.frame.transform_box(&self.object.bounding_box())
+    }
+
+    fn spans(&self, ray: &Ray) -> Vec<Span<'_>> {
+        let mut spans = self.object.spans(&self.frame.to_local(ray));
+        for span in spans.iter_mut() {
+            self.frame.to_world(&mut span.enter);
+            self.frame.to_world(&mut span.exit);
+        }
+        spans
+    }
+}
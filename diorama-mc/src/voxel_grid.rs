@@ -0,0 +1,231 @@
+use crate::aabb::Aabb;
+use crate::cube::{box_face_hit, Cube};
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::ray_intersect::{Intersect, RayIntersect};
+use nalgebra_glm::Vec3;
+
+// Identificador reservado para las celdas vacías
+pub const AIR: u16 = 0;
+
+// Rejilla densa de bloques unitarios en coordenadas enteras, recorrida con DDA (Amanatides–Woo)
+pub struct VoxelGrid {
+    pub origin: Vec3, // Esquina mínima de la celda (0, 0, 0)
+    pub size: [usize; 3],
+    cells: Vec<u16>,
+    materials: Vec<Material>, // El bloque `id` usa materials[id - 1]
+}
+
+impl VoxelGrid {
+    pub fn new(origin: Vec3, size: [usize; 3]) -> Self {
+        VoxelGrid {
+            origin,
+            size,
+            cells: vec![AIR; size[0] * size[1] * size[2]],
+            materials: Vec::new(),
+        }
+    }
+
+    // Construye la rejilla a partir de cubos alineados a la rejilla entera
+    pub fn from_cubes(cubes: &[Cube]) -> Self {
+        if cubes.is_empty() {
+            return VoxelGrid::new(Vec3::zeros(), [0, 0, 0]);
+        }
+
+        let mut min = Vec3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = Vec3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for cube in cubes {
+            min = min.inf(&cube.min);
+            max = max.sup(&cube.max);
+        }
+        let min = min.map(|c| c.round());
+        let max = max.map(|c| c.round());
+        let size = [
+            (max.x - min.x) as usize,
+            (max.y - min.y) as usize,
+            (max.z - min.z) as usize,
+        ];
+
+        let mut grid = VoxelGrid::new(min, size);
+        for cube in cubes {
+            let id = grid.add_material(cube.material.clone());
+            let lo = (cube.min - min).map(|c| c.round() as usize);
+            let hi = (cube.max - min).map(|c| c.round() as usize);
+            for x in lo.x..hi.x {
+                for y in lo.y..hi.y {
+                    for z in lo.z..hi.z {
+                        grid.set(x, y, z, id);
+                    }
+                }
+            }
+        }
+        grid
+    }
+
+    // Registra un material y devuelve su identificador de bloque (reutiliza los repetidos)
+    pub fn add_material(&mut self, material: Material) -> u16 {
+        if let Some(index) = self.materials.iter().position(|m| m.id == material.id) {
+            return index as u16 + 1;
+        }
+        assert!(self.materials.len() < u16::MAX as usize, "Too many materials in voxel grid");
+        self.materials.push(material);
+        self.materials.len() as u16
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, id: u16) {
+        if x < self.size[0] && y < self.size[1] && z < self.size[2] {
+            let index = self.index(x, y, z);
+            self.cells[index] = id;
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u16 {
+        if x < self.size[0] && y < self.size[1] && z < self.size[2] {
+            self.cells[self.index(x, y, z)]
+        } else {
+            AIR
+        }
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (y * self.size[2] + z) * self.size[0] + x
+    }
+}
+
+// Celda sólida donde se detuvo el recorrido
+struct VoxelHit {
+    t: f32,
+    cell: [i64; 3],
+    normal: Vec3,
+}
+
+impl VoxelGrid {
+    // Material de una celda sólida devuelta por la marcha
+    fn cell_material(&self, cell: &[i64; 3]) -> &Material {
+        let id = self.get(cell[0] as usize, cell[1] as usize, cell[2] as usize);
+        &self.materials[id as usize - 1]
+    }
+
+    // Recorrido DDA: devuelve la primera celda sólida dentro del rango del rayo
+    fn march(&self, ray: &Ray) -> Option<VoxelHit> {
+        if self.cells.is_empty() {
+            return None;
+        }
+
+        let inv_dir = ray.inv_direction();
+        let bounds = self.bounding_box();
+        let t_enter = bounds.hit(ray, &inv_dir)?;
+
+        // Eje por el que el rayo entra a la rejilla (None si empieza dentro)
+        let mut last_axis = None;
+        if t_enter > ray.t_min {
+            let t0 = (bounds.min - ray.origin).component_mul(&inv_dir);
+            let t1 = (bounds.max - ray.origin).component_mul(&inv_dir);
+            let near = t0.inf(&t1);
+            last_axis = Some(if near.x >= near.y && near.x >= near.z {
+                0
+            } else if near.y >= near.z {
+                1
+            } else {
+                2
+            });
+        }
+
+        let t_start = t_enter.max(ray.t_min);
+        let start = ray.at(t_start) - self.origin;
+
+        let mut cell = [0i64; 3];
+        let mut step = [0i64; 3];
+        let mut t_max = [f32::INFINITY; 3];
+        let mut t_delta = [f32::INFINITY; 3];
+
+        for axis in 0..3 {
+            cell[axis] = (start[axis].floor() as i64).clamp(0, self.size[axis] as i64 - 1);
+            if ray.direction[axis] > 0.0 {
+                step[axis] = 1;
+                let boundary = self.origin[axis] + (cell[axis] + 1) as f32;
+                t_max[axis] = (boundary - ray.origin[axis]) * inv_dir[axis];
+                t_delta[axis] = inv_dir[axis];
+            } else if ray.direction[axis] < 0.0 {
+                step[axis] = -1;
+                let boundary = self.origin[axis] + cell[axis] as f32;
+                t_max[axis] = (boundary - ray.origin[axis]) * inv_dir[axis];
+                t_delta[axis] = -inv_dir[axis];
+            }
+        }
+
+        let mut t = t_start;
+        while t < ray.t_max {
+            let id = self.get(cell[0] as usize, cell[1] as usize, cell[2] as usize);
+
+            if id != AIR {
+                let mut normal = Vec3::zeros();
+                if let Some(axis) = last_axis {
+                    normal[axis] = -step[axis] as f32;
+                    return Some(VoxelHit { t, cell, normal });
+                }
+
+                // El rayo nace dentro del bloque (p. ej. un rayo refractado dentro del hielo):
+                // el impacto es la cara por la que sale, con su normal exterior
+                let axis = nearest_axis(&t_max);
+                if t_max[axis] >= ray.t_max {
+                    return None;
+                }
+                normal[axis] = step[axis] as f32;
+                return Some(VoxelHit { t: t_max[axis], cell, normal });
+            }
+
+            // Avanzar a la celda vecina por el eje cuyo borde está más cerca
+            let axis = nearest_axis(&t_max);
+
+            cell[axis] += step[axis];
+            if cell[axis] < 0 || cell[axis] >= self.size[axis] as i64 {
+                break;
+            }
+            t = t_max[axis];
+            t_max[axis] += t_delta[axis];
+            last_axis = Some(axis);
+        }
+
+        None
+    }
+}
+
+// Eje cuyo próximo borde de celda está más cerca a lo largo del rayo
+fn nearest_axis(t_max: &[f32; 3]) -> usize {
+    if t_max[0] < t_max[1] && t_max[0] < t_max[2] {
+        0
+    } else if t_max[1] < t_max[2] {
+        1
+    } else {
+        2
+    }
+}
+
+impl RayIntersect for VoxelGrid {
+    fn ray_intersect(&self, ray: &Ray) -> Intersect<'_> {
+        match self.march(ray) {
+            Some(hit) => {
+                let point = ray.at(hit.t);
+                let cell = hit.cell;
+                let cell_min = self.origin + Vec3::new(cell[0] as f32, cell[1] as f32, cell[2] as f32);
+                let cell_max = cell_min + Vec3::new(1.0, 1.0, 1.0);
+
+                let material = self.cell_material(&cell);
+                let mut intersect = box_face_hit(&cell_min, &cell_max, point, hit.normal, hit.t, material);
+                intersect.set_face_normal(&ray.direction, &hit.normal);
+                intersect
+            }
+            None => Intersect::empty(),
+        }
+    }
+
+    fn occluder(&self, ray: &Ray) -> Option<&Material> {
+        self.march(ray).map(|hit| self.cell_material(&hit.cell))
+    }
+
+    fn bounding_box(&self) -> Aabb {
+        let size = Vec3::new(self.size[0] as f32, self.size[1] as f32, self.size[2] as f32);
+        Aabb::new(self.origin, self.origin + size)
+    }
+}